@@ -0,0 +1,58 @@
+use aoc2019_rust::assemble::assemble_to_string;
+use aoc2019_rust::{fast, intcode};
+use std::time::Instant;
+
+//counts from 1 up to the input and outputs how often the counter was odd,
+//the sign flips every step and is -1 for the odd ones
+fn busy_loop() -> String {
+    assemble_to_string(
+        "
+                in [limit]
+        loop:   add [counter], #1, [counter]
+                mul [sign], #-1, [sign]
+                eq [sign], #-1, [is_odd]
+                add [odd], [is_odd], [odd]
+                eq [counter], [limit], [done]
+                jf [done], #loop
+                out [odd]
+                hlt
+        limit:   dw 0
+        counter: dw 0
+        sign:    dw 1
+        is_odd:  dw 0
+        odd:     dw 0
+        done:    dw 0",
+    )
+    .unwrap()
+}
+
+//cargo run --release --example benchmark [program file] [inputs...]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (program, input) = match args.split_first() {
+        Some((path, inputs)) => (
+            std::fs::read_to_string(path).unwrap().trim().to_string(),
+            inputs.iter().map(|i| i.parse().unwrap()).collect(),
+        ),
+        None => (busy_loop(), vec![10_000_000]),
+    };
+
+    let start = Instant::now();
+    let expected = intcode::computer(&program, input.clone());
+    let interpreter = start.elapsed();
+
+    let start = Instant::now();
+    let actual = fast::computer(&program, input);
+    let decoded = start.elapsed();
+
+    assert_eq!(expected, actual, "the interpreters disagree");
+
+    println!("output      {:?}", actual);
+    println!("interpreter {:?}", interpreter);
+    println!("fast        {:?}", decoded);
+    println!(
+        "speedup     {:.1}x",
+        interpreter.as_secs_f64() / decoded.as_secs_f64()
+    );
+}
@@ -0,0 +1,249 @@
+use crate::intcode::{IntcodeError, Machine};
+use crate::memory::{DenseMemory, Memory};
+use permutohedron::Heap;
+use std::thread;
+
+/// Machines wired together, every output of a node is sent to each node it is
+/// connected to. Nodes run in turns in the order they were added, on the
+/// calling thread, so the wiring may contain loops.
+pub struct Topology<M: Memory = DenseMemory> {
+    nodes: Vec<Machine<M>>,
+    edges: Vec<(usize, usize)>,
+    last_outputs: Vec<Option<i64>>,
+}
+
+impl<M: Memory> Default for Topology<M> {
+    fn default() -> Topology<M> {
+        Topology {
+            nodes: vec![],
+            edges: vec![],
+            last_outputs: vec![],
+        }
+    }
+}
+
+impl<M: Memory> Topology<M> {
+    pub fn new() -> Topology<M> {
+        Topology::default()
+    }
+
+    /// Amplifiers in series, each reading its phase first, the first one also receives `signal`.
+    pub fn series(machine: &Machine<M>, phases: &[i64], signal: i64) -> Topology<M> {
+        let mut topology = Topology::new();
+
+        for (i, phase) in phases.iter().enumerate() {
+            let node = topology.add_node(machine.clone(), &[*phase]);
+            if i > 0 {
+                topology.connect(node - 1, node);
+            }
+        }
+
+        if !phases.is_empty() {
+            topology.push_input(0, signal);
+        }
+
+        topology
+    }
+
+    /// Like `series`, with the last amplifier feeding back into the first.
+    pub fn feedback_loop(machine: &Machine<M>, phases: &[i64], signal: i64) -> Topology<M> {
+        let mut topology = Topology::series(machine, phases, signal);
+
+        if !phases.is_empty() {
+            topology.connect(phases.len() - 1, 0);
+        }
+
+        topology
+    }
+
+    /// Adds a machine that reads `seed` before anything sent to it, returns its node number.
+    pub fn add_node(&mut self, mut machine: Machine<M>, seed: &[i64]) -> usize {
+        machine.extend_input(seed.iter().copied());
+
+        self.nodes.push(machine);
+        self.last_outputs.push(None);
+        self.nodes.len() - 1
+    }
+
+    /// Sends the output of `from` to `to` as well.
+    pub fn connect(&mut self, from: usize, to: usize) {
+        assert!(
+            from < self.nodes.len() && to < self.nodes.len(),
+            "can't connect {} to {}, there are {} nodes",
+            from,
+            to,
+            self.nodes.len()
+        );
+
+        self.edges.push((from, to))
+    }
+
+    pub fn push_input(&mut self, node: usize, value: i64) {
+        self.nodes[node].push_input(value)
+    }
+
+    pub fn node(&self, node: usize) -> &Machine<M> {
+        &self.nodes[node]
+    }
+
+    /// The last value `node` wrote, even if it was sent on to other nodes.
+    pub fn last_output(&self, node: usize) -> Option<i64> {
+        self.last_outputs[node]
+    }
+
+    /// Runs until every node halted or waits for input nobody is going to send,
+    /// then returns the last output of `node`.
+    pub fn run(&mut self, node: usize) -> Result<Option<i64>, IntcodeError> {
+        loop {
+            let mut progress = false;
+
+            for from in 0..self.nodes.len() {
+                //a node that halted or waits for input gives up its turn
+                self.nodes[from].run_until_input()?;
+
+                let output = self.nodes[from].take_output();
+                if let Some(last) = output.last() {
+                    progress = true;
+                    self.last_outputs[from] = Some(*last);
+                }
+
+                for &(_, to) in self.edges.iter().filter(|(f, _)| *f == from) {
+                    self.nodes[to].extend_input(output.iter().copied());
+                }
+            }
+
+            if !progress {
+                break Ok(self.last_outputs[node]);
+            }
+        }
+    }
+}
+
+/// Tries every permutation of the phases and returns the one with the highest
+/// signal together with the signal. Permutations without a signal are skipped.
+pub fn best_phases<F>(phases: &[i64], signal: F) -> Result<Option<(Vec<i64>, i64)>, IntcodeError>
+where
+    F: Fn(&[i64]) -> Result<Option<i64>, IntcodeError>,
+{
+    let mut best = None;
+
+    for permutation in permutations(phases) {
+        best = better(best, signal(&permutation)?.map(|s| (permutation, s)));
+    }
+
+    Ok(best)
+}
+
+/// Like `best_phases`, but splits the permutations over `threads` threads.
+pub fn best_phases_parallel<F>(
+    phases: &[i64],
+    threads: usize,
+    signal: F,
+) -> Result<Option<(Vec<i64>, i64)>, IntcodeError>
+where
+    F: Fn(&[i64]) -> Result<Option<i64>, IntcodeError> + Sync,
+{
+    let permutations = permutations(phases);
+    let chunk = permutations.len().div_ceil(threads.max(1)).max(1);
+    let signal = &signal;
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = permutations
+            .chunks(chunk)
+            .map(|permutations| {
+                scope.spawn(move || {
+                    let mut best = None;
+                    for permutation in permutations {
+                        let s = signal(permutation)?;
+                        best = better(best, s.map(|s| (permutation.clone(), s)));
+                    }
+                    Ok(best)
+                })
+            })
+            .collect();
+
+        let mut best = None;
+        for worker in workers {
+            //a worker only panics if the signal function does
+            let found = worker
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e))?;
+            best = better(best, found);
+        }
+
+        Ok(best)
+    })
+}
+
+fn permutations(phases: &[i64]) -> Vec<Vec<i64>> {
+    let mut phases = phases.to_vec();
+    Heap::new(&mut phases).collect()
+}
+
+//the first one wins a tie, so the result doesn't depend on the number of threads
+fn better(
+    best: Option<(Vec<i64>, i64)>,
+    candidate: Option<(Vec<i64>, i64)>,
+) -> Option<(Vec<i64>, i64)> {
+    match (best, candidate) {
+        (Some(b), Some(c)) if c.1 > b.1 => Some(c),
+        (None, c) => c,
+        (b, _) => b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::amplifier::{best_phases, best_phases_parallel, Topology};
+    use crate::intcode::tests::doubler;
+    use crate::intcode::Machine;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+    use std::thread;
+
+    #[test]
+    fn wires_a_graph() {
+        let double = doubler();
+        //adds its two inputs
+        let add = Machine::parse("3,11,3,12,1,11,12,11,4,11,99").unwrap();
+
+        //10 is doubled twice on one side and once on the other, then both are added
+        let mut topology = Topology::new();
+        let source = topology.add_node(double.clone(), &[10]);
+        let left = topology.add_node(double.clone(), &[]);
+        let right = topology.add_node(Machine::parse("3,7,4,7,1105,1,0").unwrap(), &[]);
+        let sink = topology.add_node(add, &[]);
+        topology.connect(source, left);
+        topology.connect(source, right);
+        topology.connect(left, sink);
+        topology.connect(right, sink);
+
+        assert_eq!(topology.run(sink), Ok(Some(60)));
+        assert_eq!(topology.last_output(left), Some(40));
+    }
+
+    #[test]
+    fn searches_the_phases() {
+        let machine = Machine::parse("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0").unwrap();
+        let signal = |phases: &[i64]| Topology::series(&machine, phases, 0).run(phases.len() - 1);
+
+        let expected = Some((vec![4, 3, 2, 1, 0], 43210));
+        assert_eq!(best_phases(&[0, 1, 2, 3, 4], signal), Ok(expected.clone()));
+        assert_eq!(
+            best_phases_parallel(&[0, 1, 2, 3, 4], 4, signal),
+            Ok(expected.clone())
+        );
+
+        //120 permutations over 7 threads, never more
+        let workers = Mutex::new(HashSet::new());
+        let counted = |phases: &[i64]| {
+            workers.lock().unwrap().insert(thread::current().id());
+            signal(phases)
+        };
+        assert_eq!(
+            best_phases_parallel(&[0, 1, 2, 3, 4], 7, counted),
+            Ok(expected)
+        );
+        assert_eq!(workers.lock().unwrap().len(), 7);
+    }
+}
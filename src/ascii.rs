@@ -0,0 +1,131 @@
+use crate::intcode::Machine;
+use crate::memory::Memory;
+use std::error::Error;
+use std::fmt;
+
+/// Text that can't be sent to a machine, because it isn't 7-bit ASCII.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NotAscii {
+    /// The byte offset of the character in the text.
+    pub position: usize,
+    pub character: char,
+}
+
+impl fmt::Display for NotAscii {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' at position {} isn't ASCII",
+            self.character, self.position
+        )
+    }
+}
+
+impl Error for NotAscii {}
+
+/// Converts text to Intcode input, one value per character.
+pub fn to_ascii(text: &str) -> Result<Vec<i64>, NotAscii> {
+    text.char_indices()
+        .map(|(position, character)| {
+            if character.is_ascii() {
+                Ok(character as i64)
+            } else {
+                Err(NotAscii {
+                    position,
+                    character,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Output of a machine decoded as text. Values that aren't ASCII are
+/// the answers some programs print after their text.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct AsciiText {
+    pub text: String,
+    pub answers: Vec<i64>,
+}
+
+impl AsciiText {
+    pub fn decode(values: &[i64]) -> AsciiText {
+        let mut decoded = AsciiText::default();
+
+        for &value in values {
+            decoded.push(value);
+        }
+
+        decoded
+    }
+
+    /// Adds a value to the text, or to the answers when it isn't ASCII.
+    pub fn push(&mut self, value: i64) {
+        if (0..128).contains(&value) {
+            self.text.push(value as u8 as char);
+        } else {
+            self.answers.push(value);
+        }
+    }
+
+    pub fn lines(&self) -> Vec<&str> {
+        self.text.lines().collect()
+    }
+}
+
+impl<M: Memory> Machine<M> {
+    /// Sends the text as ASCII, it isn't sent at all when a character isn't ASCII.
+    pub fn push_ascii(&mut self, text: &str) -> Result<(), NotAscii> {
+        to_ascii(text).map(|values| self.extend_input(values))
+    }
+
+    /// Sends the line followed by a newline.
+    pub fn push_line(&mut self, line: &str) -> Result<(), NotAscii> {
+        self.push_ascii(line)?;
+        self.push_input(i64::from(b'\n'));
+        Ok(())
+    }
+
+    /// Drains the output buffer, decoded as text.
+    pub fn take_ascii_output(&mut self) -> AsciiText {
+        AsciiText::decode(&self.take_output())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ascii::{to_ascii, AsciiText, NotAscii};
+    use crate::intcode::{ComputeResult, Machine};
+
+    #[test]
+    fn converts_text() {
+        assert_eq!(to_ascii("A,1\n"), Ok(vec![65, 44, 49, 10]));
+        assert_eq!(
+            to_ascii("ok→"),
+            Err(NotAscii {
+                position: 2,
+                character: '→'
+            })
+        );
+
+        let decoded = AsciiText::decode(&[35, 46, 10, 46, 35, 10, 1234, -1]);
+        assert_eq!(decoded.lines(), vec!["#.", ".#"]);
+        assert_eq!(decoded.answers, vec![1234, -1]);
+    }
+
+    #[test]
+    fn talks_to_a_machine() {
+        //echoes every character until it reads a newline, then prints 1000
+        let program = "3,100,1008,100,10,101,1005,101,16,4,100,1105,1,0,99,99,104,1000,99";
+        let mut machine = Machine::parse(program).unwrap();
+
+        assert!(machine.push_line("héllo").is_err());
+        assert!(machine.input().is_empty());
+
+        machine.push_line("hello").unwrap();
+        assert_eq!(machine.run_until_input(), Ok(ComputeResult::Halt));
+
+        let output = machine.take_ascii_output();
+        assert_eq!(output.lines(), vec!["hello"]);
+        assert_eq!(output.answers, vec![1000]);
+    }
+}
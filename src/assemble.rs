@@ -0,0 +1,649 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// An assembly error together with the (1-based) source line it was found on.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AssembleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AssembleError {}
+
+fn error<T>(line: usize, message: String) -> Result<T, AssembleError> {
+    Err(AssembleError { line, message })
+}
+
+//a number, or a label or constant with an optional offset
+#[derive(Clone, Debug)]
+enum Expression {
+    Number(i64),
+    Symbol(String, i64),
+}
+
+#[derive(Clone, Debug)]
+struct Parameter {
+    mode: i64,
+    value: Expression,
+}
+
+enum Statement {
+    Instruction(i64, Vec<Parameter>),
+    Data(Vec<Expression>),
+}
+
+struct Macro {
+    parameters: Vec<String>,
+    body: Vec<String>,
+}
+
+//the opcode, the number of parameters and which of them is written to
+fn opcode(mnemonic: &str) -> Option<(i64, usize, Option<usize>)> {
+    match mnemonic {
+        "add" => Some((1, 3, Some(2))),
+        "mul" => Some((2, 3, Some(2))),
+        "in" => Some((3, 1, Some(0))),
+        "out" => Some((4, 1, None)),
+        "jt" => Some((5, 2, None)),
+        "jf" => Some((6, 2, None)),
+        "lt" => Some((7, 3, Some(2))),
+        "eq" => Some((8, 3, Some(2))),
+        "arb" => Some((9, 1, None)),
+        "hlt" => Some((99, 0, None)),
+        _ => None,
+    }
+}
+
+fn is_identifier(string: &str) -> bool {
+    let mut chars = string.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+//removes a `;` comment, unless it is inside a string literal
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && in_string {
+            escaped = true;
+        } else if c == '"' {
+            in_string = !in_string;
+        } else if c == ';' && !in_string {
+            return &line[..i];
+        }
+    }
+
+    line
+}
+
+//splits on commas outside of string literals
+fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut parts = vec![];
+
+    for (i, c) in arguments.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && in_string {
+            escaped = true;
+        } else if c == '"' {
+            in_string = !in_string;
+        } else if c == ',' && !in_string {
+            parts.push(arguments[start..i].trim());
+            start = i + 1;
+        }
+    }
+
+    parts.push(arguments[start..].trim());
+    parts.into_iter().filter(|p| !p.is_empty()).collect()
+}
+
+fn split_mnemonic(line: &str) -> (&str, &str) {
+    match line.find(char::is_whitespace) {
+        Some(i) => (&line[..i], line[i..].trim()),
+        None => (line, ""),
+    }
+}
+
+fn parse_number(string: &str) -> Option<i64> {
+    string.parse().ok()
+}
+
+fn parse_expression(string: &str, line: usize) -> Result<Expression, AssembleError> {
+    let string = string.trim();
+
+    if let Some(n) = parse_number(string) {
+        return Ok(Expression::Number(n));
+    }
+
+    //a symbol with an offset, like `buffer+2` or `end-1`
+    let (symbol, offset) = match string.rfind(['+', '-']) {
+        Some(i) if i > 0 => match parse_number(string[i..].trim_start_matches('+').trim()) {
+            Some(offset) => (string[..i].trim(), offset),
+            None => return error(line, format!("invalid offset in '{}'", string)),
+        },
+        _ => (string, 0),
+    };
+
+    if is_identifier(symbol) {
+        Ok(Expression::Symbol(symbol.to_string(), offset))
+    } else {
+        error(line, format!("invalid expression '{}'", string))
+    }
+}
+
+fn parse_parameter(string: &str, line: usize) -> Result<Parameter, AssembleError> {
+    //position mode
+    if string.starts_with('[') && string.ends_with(']') {
+        parse_expression(&string[1..string.len() - 1], line)
+            .map(|value| Parameter { mode: 0, value })
+    }
+    //immediate mode
+    else if let Some(value) = string.strip_prefix('#') {
+        parse_expression(value, line).map(|value| Parameter { mode: 1, value })
+    }
+    //relative mode
+    else if let Some(offset) = string.strip_prefix("rb") {
+        let offset = offset.trim();
+
+        let value = if offset.is_empty() {
+            Expression::Number(0)
+        } else if let Some(offset) = offset.strip_prefix('+') {
+            parse_expression(offset, line)?
+        } else if let Some(offset) = offset.strip_prefix('-') {
+            match parse_expression(offset, line)? {
+                Expression::Number(n) => Expression::Number(-n),
+                Expression::Symbol(..) => {
+                    return error(line, format!("can't negate a symbol in '{}'", string))
+                }
+            }
+        } else {
+            return error(line, format!("invalid relative parameter '{}'", string));
+        };
+
+        Ok(Parameter { mode: 2, value })
+    } else {
+        error(
+            line,
+            format!(
+                "parameter '{}' must be [position], #immediate or rb+relative",
+                string
+            ),
+        )
+    }
+}
+
+fn parse_string_literal(string: &str, line: usize) -> Result<Vec<Expression>, AssembleError> {
+    let inner = &string[1..string.len() - 1];
+    let mut values = vec![];
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            match chars.next() {
+                Some('n') => '\n',
+                Some('\\') => '\\',
+                Some('"') => '"',
+                other => return error(line, format!("unknown escape sequence {:?}", other)),
+            }
+        } else {
+            c
+        };
+
+        values.push(Expression::Number(c as i64));
+    }
+
+    Ok(values)
+}
+
+fn parse_data(
+    arguments: &str,
+    line: usize,
+    strings: bool,
+) -> Result<Vec<Expression>, AssembleError> {
+    let mut values = vec![];
+
+    for argument in split_arguments(arguments) {
+        if argument.len() >= 2 && argument.starts_with('"') && argument.ends_with('"') {
+            if !strings {
+                return error(line, "strings are only allowed with db".to_string());
+            }
+
+            values.extend(parse_string_literal(argument, line)?);
+        } else {
+            values.push(parse_expression(argument, line)?);
+        }
+    }
+
+    Ok(values)
+}
+
+//replaces every whole-word occurrence of a macro parameter with its argument, outside of string literals
+fn substitute(body: &str, parameters: &[String], arguments: &[&str]) -> String {
+    let mut result = String::new();
+    let mut word = String::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    let flush = |word: &mut String, result: &mut String| {
+        match parameters.iter().position(|p| p == word) {
+            Some(i) => result.push_str(arguments[i]),
+            None => result.push_str(word),
+        }
+        word.clear();
+    };
+
+    for c in body.chars() {
+        if in_string {
+            result.push(c);
+
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut result);
+            result.push(c);
+            in_string = c == '"';
+        }
+    }
+    flush(&mut word, &mut result);
+
+    result
+}
+
+const MAX_MACRO_DEPTH: usize = 16;
+
+//expands macro invocations, keeping the line number of the invocation
+fn expand(
+    source_line: usize,
+    text: &str,
+    macros: &HashMap<String, Macro>,
+    depth: usize,
+    lines: &mut Vec<(usize, String)>,
+) -> Result<(), AssembleError> {
+    let mut text = text;
+
+    //keep labels in front of a macro invocation on their own line
+    while let Some(i) = text.find(':') {
+        if !is_identifier(text[..i].trim()) {
+            break;
+        }
+
+        lines.push((source_line, text[..=i].to_string()));
+        text = text[i + 1..].trim();
+    }
+
+    let (mnemonic, arguments) = split_mnemonic(text);
+
+    match macros.get(mnemonic) {
+        Some(m) => {
+            if depth >= MAX_MACRO_DEPTH {
+                return error(
+                    source_line,
+                    format!("macro '{}' nested too deeply", mnemonic),
+                );
+            }
+
+            let arguments = split_arguments(arguments);
+
+            if arguments.len() != m.parameters.len() {
+                return error(
+                    source_line,
+                    format!(
+                        "macro '{}' expects {} arguments, {} given",
+                        mnemonic,
+                        m.parameters.len(),
+                        arguments.len()
+                    ),
+                );
+            }
+
+            for body_line in &m.body {
+                let expanded = substitute(body_line, &m.parameters, &arguments);
+                expand(source_line, &expanded, macros, depth + 1, lines)?;
+            }
+
+            Ok(())
+        }
+        None => {
+            lines.push((source_line, text.to_string()));
+            Ok(())
+        }
+    }
+}
+
+//collects macro definitions and returns all other lines with macros expanded
+fn preprocess(source: &str) -> Result<Vec<(usize, String)>, AssembleError> {
+    let mut macros: HashMap<String, Macro> = HashMap::new();
+    let mut definition: Option<(usize, String, Macro)> = None;
+    let mut lines = vec![];
+
+    for (i, line) in source.lines().enumerate() {
+        let number = i + 1;
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let (keyword, arguments) = split_mnemonic(line);
+
+        if let Some((_, name, m)) = definition.as_mut() {
+            if keyword == "endm" {
+                let (_, name, m) = definition.take().unwrap();
+                macros.insert(name, m);
+            } else if keyword == "macro" {
+                return error(number, format!("macro defined inside macro '{}'", name));
+            } else {
+                m.body.push(line.to_string());
+            }
+        } else if keyword == "macro" {
+            let (name, parameters) = split_mnemonic(arguments);
+
+            if !is_identifier(name) || opcode(name).is_some() {
+                return error(number, format!("invalid macro name '{}'", name));
+            }
+
+            let parameters: Vec<String> = split_arguments(parameters)
+                .into_iter()
+                .map(|p| p.to_string())
+                .collect();
+
+            if let Some(p) = parameters.iter().find(|p| !is_identifier(p)) {
+                return error(number, format!("invalid macro parameter '{}'", p));
+            }
+
+            definition = Some((
+                number,
+                name.to_string(),
+                Macro {
+                    parameters,
+                    body: vec![],
+                },
+            ));
+        } else if keyword == "endm" {
+            return error(number, "endm without macro".to_string());
+        } else {
+            expand(number, line, &macros, 0, &mut lines)?;
+        }
+    }
+
+    match definition {
+        Some((number, name, _)) => error(number, format!("macro '{}' is missing endm", name)),
+        None => Ok(lines),
+    }
+}
+
+/// Assembles a program into Intcode.
+///
+/// Each line holds an optional `label:` followed by an instruction, a directive
+/// or nothing, and `;` starts a comment. Instructions use the mnemonics of the
+/// disassembler (`add`, `mul`, `in`, `out`, `jt`, `jf`, `lt`, `eq`, `arb`, `hlt`)
+/// with `[address]`, `#value` and `rb+offset` parameters, where addresses and
+/// values can be numbers, labels or constants with an optional `+n`/`-n` offset.
+///
+/// Directives:
+/// * `const NAME = value` defines a constant,
+/// * `dw 1, 2, label` emits values,
+/// * `db "text\n", 0` emits values and strings as one ASCII code per cell,
+/// * `macro name a, b` ... `endm` defines a macro that is invoked like an instruction.
+pub fn assemble(source: &str) -> Result<Vec<i64>, AssembleError> {
+    let mut symbols: HashMap<String, i64> = HashMap::new();
+    let mut statements: Vec<(usize, Statement)> = vec![];
+    let mut address = 0;
+
+    //first pass, parse the statements and assign addresses to the labels
+    for (number, line) in preprocess(source)? {
+        let mut line = line.as_str();
+
+        while let Some(i) = line.find(':') {
+            let label = line[..i].trim();
+
+            if !is_identifier(label) {
+                break;
+            }
+            if symbols.insert(label.to_string(), address).is_some() {
+                return error(number, format!("'{}' is defined more than once", label));
+            }
+
+            line = line[i + 1..].trim();
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, arguments) = split_mnemonic(line);
+
+        let statement = if mnemonic == "const" {
+            let mut parts = arguments.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts.next().and_then(|v| parse_number(v.trim()));
+
+            match value {
+                Some(value) if is_identifier(name) => {
+                    if symbols.insert(name.to_string(), value).is_some() {
+                        return error(number, format!("'{}' is defined more than once", name));
+                    }
+                    continue;
+                }
+                _ => return error(number, format!("invalid constant '{}'", arguments)),
+            }
+        } else if mnemonic == "dw" || mnemonic == "db" {
+            Statement::Data(parse_data(arguments, number, mnemonic == "db")?)
+        } else {
+            let (opcode, count, writes) = match opcode(mnemonic) {
+                Some(o) => o,
+                None => return error(number, format!("unknown mnemonic '{}'", mnemonic)),
+            };
+
+            let parameters = split_arguments(arguments)
+                .into_iter()
+                .map(|p| parse_parameter(p, number))
+                .collect::<Result<Vec<Parameter>, AssembleError>>()?;
+
+            if parameters.len() != count {
+                return error(
+                    number,
+                    format!(
+                        "'{}' expects {} parameters, {} given",
+                        mnemonic,
+                        count,
+                        parameters.len()
+                    ),
+                );
+            }
+
+            if let Some(w) = writes {
+                if parameters[w].mode == 1 {
+                    return error(
+                        number,
+                        format!("'{}' can't write to an immediate parameter", mnemonic),
+                    );
+                }
+            }
+
+            Statement::Instruction(opcode, parameters)
+        };
+
+        address += match &statement {
+            Statement::Instruction(_, parameters) => parameters.len() as i64 + 1,
+            Statement::Data(values) => values.len() as i64,
+        };
+
+        statements.push((number, statement));
+    }
+
+    //second pass, resolve the symbols and emit the Intcode
+    let resolve = |expression: &Expression, number: usize| match expression {
+        Expression::Number(n) => Ok(*n),
+        Expression::Symbol(name, offset) => match symbols.get(name) {
+            Some(value) => match value.checked_add(*offset) {
+                Some(v) => Ok(v),
+                None => error(number, format!("'{}{:+}' overflows", name, offset)),
+            },
+            None => error(number, format!("undefined symbol '{}'", name)),
+        },
+    };
+
+    let mut intcode = vec![];
+
+    for (number, statement) in statements {
+        match statement {
+            Statement::Instruction(opcode, parameters) => {
+                let modes = parameters
+                    .iter()
+                    .rev()
+                    .fold(0, |modes, parameter| modes * 10 + parameter.mode);
+
+                intcode.push(modes * 100 + opcode);
+
+                for parameter in parameters {
+                    intcode.push(resolve(&parameter.value, number)?);
+                }
+            }
+            Statement::Data(values) => {
+                for value in values {
+                    intcode.push(resolve(&value, number)?);
+                }
+            }
+        }
+    }
+
+    Ok(intcode)
+}
+
+/// Assembles a program into the comma-separated form that `computer` consumes.
+pub fn assemble_to_string(source: &str) -> Result<String, AssembleError> {
+    assemble(source).map(|intcode| {
+        intcode
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assemble::{assemble, assemble_to_string, AssembleError};
+    use crate::intcode::computer;
+
+    #[test]
+    fn assembles_instructions() {
+        assert_eq!(
+            assemble_to_string("mul [4], #3, [4]\ndw 33"),
+            Ok("1002,4,3,4,33".to_string())
+        );
+        assert_eq!(
+            assemble("arb #1\nout rb-1\nadd [100], #1, [100]\nhlt"),
+            Ok(vec![109, 1, 204, -1, 1001, 100, 1, 100, 99])
+        );
+    }
+
+    #[test]
+    fn resolves_labels_and_constants() {
+        let source = "
+            ; count down from START and print every number
+            const START = 3
+                    add #START, #0, [counter]
+            loop:   out [counter]
+                    add [counter], #-1, [counter]
+                    jt [counter], #loop
+                    hlt
+            counter: dw 0
+        ";
+
+        let intcode = assemble_to_string(source).unwrap();
+
+        assert_eq!(computer(&intcode, vec![]), Ok(vec![3, 2, 1]));
+    }
+
+    #[test]
+    fn expands_macros_and_strings() {
+        let source = "
+            macro print address
+                out [address]
+            endm
+            macro print_twice address
+                print address
+                print address
+            endm
+            start:  print_twice text+1
+                    hlt
+            text: db \"hi\\n\", 0
+        ";
+
+        let intcode = assemble_to_string(source).unwrap();
+
+        assert_eq!(computer(&intcode, vec![]), Ok(vec![105, 105]));
+
+        //parameters aren't replaced inside strings
+        let source = "
+            macro message x
+                dw x
+                db \"x\\\"x\"
+            endm
+            message 7
+        ";
+        assert_eq!(assemble(source), Ok(vec![7, 120, 34, 120]));
+
+        //an escaped quote doesn't end the string, so neither `;` nor `,` split it
+        assert_eq!(
+            assemble("db \"a\\\";b,\", 1 ; comment"),
+            Ok(vec![97, 34, 59, 98, 44, 1])
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        assert_eq!(
+            assemble("hlt\nfoo [1]"),
+            Err(AssembleError {
+                line: 2,
+                message: "unknown mnemonic 'foo'".to_string()
+            })
+        );
+        assert_eq!(assemble("\n\nin #3").unwrap_err().line, 3);
+        assert_eq!(
+            assemble("jt #1, #nowhere").unwrap_err().message,
+            "undefined symbol 'nowhere'"
+        );
+        assert_eq!(
+            assemble("add [1], [2]").unwrap_err().message,
+            "'add' expects 3 parameters, 2 given"
+        );
+        assert_eq!(assemble("macro m\nhlt").unwrap_err().line, 1);
+        assert_eq!(
+            assemble("const X = 9223372036854775807\ndw X+1"),
+            Err(AssembleError {
+                line: 2,
+                message: "'X+1' overflows".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::Memory;
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::{Future, Sink, SinkExt, Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// How many instructions a machine executes before it lets the other tasks
+/// on the executor run, so a busy machine can't starve them.
+pub const INSTRUCTIONS_PER_YIELD: u32 = 1024;
+
+//returns pending once, so the executor gets a chance to poll other tasks first
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Runs the machine as a task, reading input from the stream and writing output
+/// to the sink. It finishes when the machine halts, the input stream ends or the
+/// sink is closed, and returns the machine in its final state.
+pub async fn run_async<M, I, O>(
+    mut machine: Machine<M>,
+    mut input: I,
+    mut output: O,
+) -> Result<Machine<M>, IntcodeError>
+where
+    M: Memory,
+    I: Stream<Item = i64> + Unpin,
+    O: Sink<i64> + Unpin,
+{
+    let mut executed = 0;
+
+    loop {
+        let result = machine.step()?;
+
+        for value in machine.take_output() {
+            if output.send(value).await.is_err() {
+                return Ok(machine);
+            }
+        }
+
+        match result {
+            ComputeResult::CanContinue => {
+                executed += 1;
+                if executed % INSTRUCTIONS_PER_YIELD == 0 {
+                    YieldNow(false).await;
+                }
+            }
+            ComputeResult::WaitingForInput => match input.next().await {
+                Some(value) => machine.push_input(value),
+                None => return Ok(machine),
+            },
+            ComputeResult::Halt => return Ok(machine),
+        }
+    }
+}
+
+/// Connects a machine to channels, returning the input sink, the output stream
+/// and the task that runs the machine, which still has to be spawned or awaited.
+pub fn connect<M: Memory>(
+    machine: Machine<M>,
+) -> (
+    UnboundedSender<i64>,
+    UnboundedReceiver<i64>,
+    impl Future<Output = Result<Machine<M>, IntcodeError>>,
+) {
+    let (input, rx_input) = mpsc::unbounded();
+    let (tx_output, output) = mpsc::unbounded();
+
+    (input, output, run_async(machine, rx_input, tx_output))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asynchronous::{connect, run_async};
+    use crate::intcode::tests::doubler;
+    use crate::intcode::Machine;
+    use futures::channel::mpsc;
+    use futures::executor::{block_on, LocalPool};
+    use futures::task::LocalSpawnExt;
+    use futures::{stream, SinkExt, StreamExt};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    //adds one to every input
+    fn increment() -> Machine {
+        Machine::parse("3,11,1001,11,1,11,4,11,1105,1,0").unwrap()
+    }
+
+    #[test]
+    fn runs_with_streams() {
+        let machine = doubler();
+        let (tx, rx) = mpsc::unbounded();
+
+        block_on(run_async(machine, stream::iter(vec![1, 2, 3]), tx)).unwrap();
+
+        assert_eq!(block_on(rx.collect::<Vec<i64>>()), vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn runs_hundreds_of_machines_on_one_thread() {
+        let mut pool = LocalPool::new();
+        let spawner = pool.spawner();
+
+        let (mut first, mut previous, task) = connect(increment());
+        spawner
+            .spawn_local(async { task.await.map(|_| ()).unwrap() })
+            .unwrap();
+
+        for _ in 1..500 {
+            let (tx, rx) = mpsc::unbounded();
+            let task = run_async(increment(), previous, tx);
+            spawner
+                .spawn_local(async { task.await.map(|_| ()).unwrap() })
+                .unwrap();
+            previous = rx;
+        }
+
+        let result = pool.run_until(async move {
+            first.send(0).await.unwrap();
+            previous.next().await
+        });
+
+        assert_eq!(result, Some(500));
+    }
+
+    #[test]
+    fn runs_a_feedback_loop() {
+        let program =
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+        let mut pool = LocalPool::new();
+        let spawner = pool.spawner();
+
+        let (inputs, receivers): (Vec<_>, Vec<_>) = vec![9, 8, 7, 6, 5]
+            .into_iter()
+            .map(|phase| {
+                let (tx, rx) = mpsc::unbounded();
+                tx.unbounded_send(phase).unwrap();
+                (tx, rx)
+            })
+            .unzip();
+        inputs[0].unbounded_send(0).unwrap();
+
+        let (tx_thrust, mut rx_thrust) = mpsc::unbounded();
+
+        for (i, input) in receivers.into_iter().enumerate() {
+            let output = inputs.get(i + 1).unwrap_or(&tx_thrust).clone();
+            let task = run_async(Machine::parse(program).unwrap(), input, output);
+            spawner
+                .spawn_local(async { task.await.map(|_| ()).unwrap() })
+                .unwrap();
+        }
+        drop(tx_thrust);
+
+        //pass the output of the last amplifier back to the first, remembering it
+        let last = Rc::new(Cell::new(0));
+        let thrust = Rc::clone(&last);
+        let first = inputs[0].clone();
+        spawner
+            .spawn_local(async move {
+                while let Some(v) = rx_thrust.next().await {
+                    thrust.set(v);
+                    let _ = first.unbounded_send(v);
+                }
+            })
+            .unwrap();
+
+        pool.run();
+        assert_eq!(last.get(), 139629729);
+    }
+}
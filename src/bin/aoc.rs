@@ -0,0 +1,126 @@
+use aoc2019_rust::days::puzzles;
+use aoc2019_rust::solver::{run, Check, Puzzle};
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "usage: aoc [list | <day>...]
+
+Solves the given days, or every day there's a solver for, with the inputs in
+the inputs directory and checks the answers against the expected ones.
+The exit status is 1 when a day can't be solved or an answer is wrong. Answers
+without an expected one to check against are marked '?' and don't count as
+failures.";
+
+fn format_time(time: Duration) -> String {
+    if time.as_secs() > 0 {
+        format!("{:.2}s", time.as_secs_f64())
+    } else {
+        format!("{:.2}ms", time.as_secs_f64() * 1000.0)
+    }
+}
+
+fn selected(
+    arguments: &[String],
+    puzzles: Vec<Box<dyn Puzzle>>,
+) -> Result<Vec<Box<dyn Puzzle>>, String> {
+    if arguments.is_empty() {
+        return Ok(puzzles);
+    }
+
+    let days = arguments
+        .iter()
+        .map(|a| a.parse().map_err(|_| format!("'{}' isn't a day", a)))
+        .collect::<Result<Vec<u32>, String>>()?;
+
+    if let Some(day) = days
+        .iter()
+        .find(|d| !puzzles.iter().any(|p| p.day() == **d))
+    {
+        return Err(format!("there's no solver for day {}", day));
+    }
+
+    Ok(puzzles
+        .into_iter()
+        .filter(|p| days.contains(&p.day()))
+        .collect())
+}
+
+fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+
+    match arguments.first().map(String::as_str) {
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            return;
+        }
+        Some("list") => {
+            for puzzle in puzzles() {
+                println!("day {}", puzzle.day());
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let puzzles = match selected(&arguments, puzzles()) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    println!(
+        "{:>3} {:>4}  {:<20} {:<8} {:>10}",
+        "day", "part", "answer", "check", "time"
+    );
+
+    let mut failed = 0;
+    let mut total = Duration::default();
+
+    for puzzle in puzzles {
+        let solution = match run(puzzle.as_ref()) {
+            Ok(s) => s,
+            Err(e) => {
+                failed += 1;
+                println!("{:>3}       {}", puzzle.day(), e);
+                continue;
+            }
+        };
+
+        total += solution.parse_time;
+
+        for part in &solution.parts {
+            total += part.time;
+
+            let (answer, check) = match (&part.answer, &part.check) {
+                (Ok(a), Check::Correct) => (a.clone(), "ok".to_string()),
+                (Ok(a), Check::Wrong { expected }) => (a.clone(), format!("expected {}", expected)),
+                (Ok(a), Check::Unknown) => (a.clone(), "?".to_string()),
+                (Err(e), _) => (e.clone(), "failed".to_string()),
+            };
+
+            if !matches!(
+                (&part.answer, &part.check),
+                (Ok(_), Check::Correct | Check::Unknown)
+            ) {
+                failed += 1;
+            }
+
+            println!(
+                "{:>3} {:>4}  {:<20} {:<8} {:>10}",
+                solution.day,
+                part.part,
+                answer,
+                check,
+                format_time(part.time)
+            );
+        }
+    }
+
+    println!("{} failed, {} in total", failed, format_time(total));
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,195 @@
+use aoc2019_rust::debugger::{Debugger, StopReason};
+use aoc2019_rust::disassemble::Instruction;
+use aoc2019_rust::intcode::{IntcodeError, Machine};
+use aoc2019_rust::memory::Memory;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "commands:
+  s                  step one instruction
+  n                  step over a call
+  c                  continue until a breakpoint, watchpoint, halt or missing input
+  b <address>        set a breakpoint      db <address>   delete a breakpoint
+  w <address>        set a watchpoint      dw <address>   delete a watchpoint
+  r                  show registers, breakpoints, watchpoints and I/O
+  l [count]          list the instructions from the instruction pointer on
+  x <address> [n]    dump n memory cells
+  set <address> <v>  write v to memory
+  ip <v>             set the instruction pointer
+  rb <v>             set the relative base
+  i <v> [v...]       provide input
+  o                  take and show the output
+  q                  quit";
+
+fn describe(reason: StopReason) -> String {
+    match reason {
+        StopReason::Stepped => "stepped".to_string(),
+        StopReason::Breakpoint(address) => format!("breakpoint at {}", address),
+        StopReason::Watchpoint { address, old, new } => {
+            format!("watchpoint at {} changed from {} to {}", address, old, new)
+        }
+        StopReason::Halt => "halted".to_string(),
+        StopReason::WaitingForInput => "waiting for input".to_string(),
+    }
+}
+
+fn list(debugger: &Debugger, count: usize) -> String {
+    let memory = debugger.machine().memory();
+    let mut address = debugger.machine().instruction_pointer() as usize;
+    let mut lines = vec![];
+
+    for _ in 0..count {
+        match Instruction::decode_from(memory, address) {
+            Some(instruction) => {
+                lines.push(format!("{:>6}: {}", address, instruction));
+                address += instruction.size();
+            }
+            None => {
+                lines.push(format!("{:>6}: data {}", address, memory.read(address)));
+                address += 1;
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn registers(debugger: &Debugger) -> String {
+    let machine = debugger.machine();
+
+    format!(
+        "ip {}  rb {}\nbreakpoints {:?}\nwatchpoints {:?}\ninput {:?}\noutput {:?}",
+        machine.instruction_pointer(),
+        machine.relative_base(),
+        debugger.breakpoints(),
+        debugger.watchpoints(),
+        machine.input(),
+        machine.output()
+    )
+}
+
+fn number<T: std::str::FromStr>(argument: Option<&&str>) -> Result<T, String> {
+    match argument {
+        Some(a) => a.parse().map_err(|_| format!("'{}' isn't a number", a)),
+        None => Err("missing argument".to_string()),
+    }
+}
+
+fn execute(debugger: &mut Debugger, command: &str, arguments: &[&str]) -> Result<String, String> {
+    let run = |r: Result<StopReason, IntcodeError>| r.map(describe).map_err(|e| e.to_string());
+
+    match command {
+        "s" => run(debugger.step()),
+        "n" => run(debugger.step_over()),
+        "c" => run(debugger.resume()),
+        "b" => number(arguments.first()).map(|a| {
+            debugger.add_breakpoint(a);
+            format!("breakpoint at {}", a)
+        }),
+        "db" => number(arguments.first()).map(|a| {
+            debugger.remove_breakpoint(a);
+            format!("deleted breakpoint at {}", a)
+        }),
+        "w" => number(arguments.first()).map(|a| {
+            debugger.add_watchpoint(a);
+            format!("watchpoint at {}", a)
+        }),
+        "dw" => number(arguments.first()).map(|a| {
+            debugger.remove_watchpoint(a);
+            format!("deleted watchpoint at {}", a)
+        }),
+        "r" => Ok(registers(debugger)),
+        "l" => {
+            let count = number(arguments.first()).unwrap_or(5);
+            Ok(list(debugger, count))
+        }
+        "x" => {
+            let address = number(arguments.first())?;
+            let count = number(arguments.get(1)).unwrap_or(8);
+            match debugger.dump(address, count) {
+                Ok(cells) => Ok(cells.trim_end().to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        "set" => {
+            let address = number(arguments.first())?;
+            let value = number(arguments.get(1))?;
+            debugger
+                .machine_mut()
+                .set_memory(address, value)
+                .map_err(|e| e.to_string())?;
+            Ok(format!("[{}] = {}", address, value))
+        }
+        "ip" => number(arguments.first()).map(|v| {
+            debugger.machine_mut().set_instruction_pointer(v);
+            format!("ip = {}", v)
+        }),
+        "rb" => number(arguments.first()).map(|v| {
+            debugger.machine_mut().set_relative_base(v);
+            format!("rb = {}", v)
+        }),
+        "i" => {
+            let values = arguments
+                .iter()
+                .map(|a| number(Some(a)))
+                .collect::<Result<Vec<i64>, String>>()?;
+            debugger.machine_mut().extend_input(values);
+            Ok(format!("input {:?}", debugger.machine().input()))
+        }
+        "o" => Ok(format!("{:?}", debugger.machine_mut().take_output())),
+        "h" | "help" => Ok(HELP.to_string()),
+        _ => Err(format!("unknown command '{}', try 'h'", command)),
+    }
+}
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(p) => p,
+        None => {
+            eprintln!("usage: intcode-debug <program>");
+            std::process::exit(2);
+        }
+    };
+
+    let machine = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|program| Machine::parse(program.trim()).map_err(|e| e.to_string()));
+
+    let mut debugger = match machine {
+        Ok(m) => Debugger::new(m),
+        Err(e) => {
+            eprintln!("can't load {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", list(&debugger, 1));
+
+    let stdin = io::stdin();
+
+    loop {
+        print!("(icdb) ");
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.split_first() {
+            Some((&"q", _)) => break,
+            Some((command, arguments)) => match execute(&mut debugger, command, arguments) {
+                Ok(message) => {
+                    println!("{}", message);
+
+                    if ["s", "n", "c"].contains(command) {
+                        println!("{}", list(&debugger, 1));
+                    }
+                }
+                Err(e) => println!("error: {}", e),
+            },
+            None => continue,
+        }
+    }
+}
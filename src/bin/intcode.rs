@@ -0,0 +1,459 @@
+use aoc2019_rust::ascii::to_ascii;
+use aoc2019_rust::intcode::{str_to_intcode, ComputeResult, Machine};
+use aoc2019_rust::io::{InputSource, OutputSink};
+use aoc2019_rust::program;
+use aoc2019_rust::trace::TraceWriter;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read, Write};
+use std::process;
+
+const USAGE: &str = "usage: intcode [options] [program]
+
+Runs the Intcode in the program file, or read from stdin when it is missing or
+'-'. The program is text, with comments and a header if you like, or binary.
+Input is taken from the options first, then from stdin unless the program was
+read from there.
+
+options:
+  -i, --input <v,v...>   provide input, may be repeated
+  -f, --input-file <f>   provide the numbers in the file as input,
+                         or its text with --ascii
+  -a, --ascii            read input as text, one character per value,
+                         and print ASCII output
+  -o, --output <format>  print output as 'numeric' (one per line),
+                         'ascii' or 'json'
+  -s, --max-steps <n>    stop after executing n instructions
+  -t, --trace            write every executed instruction to stderr
+  -d, --dump <file>      write the memory to the file when the program
+                         stops, '-' for stderr
+  -h, --help             show this message
+
+exit status:
+  0  the program halted
+  1  the program failed
+  2  the arguments or the program are invalid
+  3  the program is waiting for input nobody is going to give
+  4  the step limit was reached";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Format {
+    Numeric,
+    Ascii,
+    Json,
+}
+
+#[derive(Debug)]
+struct Options {
+    program: Option<String>,
+    input: Vec<i64>,
+    input_file: Option<String>,
+    ascii: bool,
+    format: Option<Format>,
+    max_steps: Option<u64>,
+    trace: bool,
+    dump: Option<String>,
+    help: bool,
+}
+
+/// Why the program stopped.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Status {
+    Halted,
+    Failed,
+    WaitingForInput,
+    StepLimit,
+}
+
+impl Status {
+    fn name(self) -> &'static str {
+        match self {
+            Status::Halted => "halted",
+            Status::Failed => "failed",
+            Status::WaitingForInput => "waiting for input",
+            Status::StepLimit => "step limit",
+        }
+    }
+
+    fn exit_code(self) -> i32 {
+        match self {
+            Status::Halted => 0,
+            Status::Failed => 1,
+            Status::WaitingForInput => 3,
+            Status::StepLimit => 4,
+        }
+    }
+}
+
+fn numbers(text: &str) -> Result<Vec<i64>, String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| format!("'{}' isn't a number", s)))
+        .collect()
+}
+
+fn parse_options(arguments: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        program: None,
+        input: vec![],
+        input_file: None,
+        ascii: false,
+        format: None,
+        max_steps: None,
+        trace: false,
+        dump: None,
+        help: false,
+    };
+
+    let mut arguments = arguments.iter();
+
+    while let Some(argument) = arguments.next() {
+        let mut value = || {
+            arguments
+                .next()
+                .ok_or_else(|| format!("{} needs a value", argument))
+        };
+
+        match argument.as_str() {
+            "-i" | "--input" => options.input.extend(numbers(value()?)?),
+            "-f" | "--input-file" => options.input_file = Some(value()?.clone()),
+            "-a" | "--ascii" => options.ascii = true,
+            "-o" | "--output" => {
+                options.format = Some(match value()?.as_str() {
+                    "numeric" => Format::Numeric,
+                    "ascii" => Format::Ascii,
+                    "json" => Format::Json,
+                    f => return Err(format!("unknown output format '{}'", f)),
+                })
+            }
+            "-s" | "--max-steps" => {
+                let steps = value()?;
+                options.max_steps = Some(
+                    steps
+                        .parse()
+                        .map_err(|_| format!("'{}' isn't a number of steps", steps))?,
+                )
+            }
+            "-t" | "--trace" => options.trace = true,
+            "-d" | "--dump" => options.dump = Some(value()?.clone()),
+            "-h" | "--help" => options.help = true,
+            a if a.starts_with('-') && a != "-" => return Err(format!("unknown option '{}'", a)),
+            a if options.program.is_none() => options.program = Some(a.to_string()),
+            a => return Err(format!("unexpected argument '{}'", a)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Reads input from stdin a line at a time, as numbers or as text.
+struct StdinInput {
+    ascii: bool,
+    pending: VecDeque<i64>,
+}
+
+impl InputSource for StdinInput {
+    fn input(&mut self) -> Option<i64> {
+        while self.pending.is_empty() {
+            let mut line = String::new();
+            if io::stdin().lock().read_line(&mut line).unwrap_or(0) == 0 {
+                return None;
+            }
+
+            let values = if self.ascii {
+                to_ascii(&line).map_err(|e| e.to_string())
+            } else {
+                numbers(&line)
+            };
+
+            match values {
+                Ok(values) => self.pending.extend(values),
+                Err(e) => eprintln!("ignored input: {}", e),
+            }
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+/// Prints every output as soon as it is written, JSON is printed when the program stops.
+struct Printer {
+    format: Format,
+    values: Vec<i64>,
+}
+
+impl OutputSink for Printer {
+    fn output(&mut self, value: i64) {
+        match self.format {
+            Format::Numeric => println!("{}", value),
+            //values that don't fit a character are the answers
+            Format::Ascii if (0..128).contains(&value) => print!("{}", value as u8 as char),
+            Format::Ascii => println!("{}", value),
+            Format::Json => self.values.push(value),
+        }
+    }
+}
+
+//a JSON string literal, with quotes, backslashes and control characters escaped
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+impl Printer {
+    fn finish(&self, status: Status, error: Option<String>) {
+        if self.format == Format::Json {
+            let values: Vec<String> = self.values.iter().map(|v| v.to_string()).collect();
+            let error = match error {
+                Some(e) => format!(",\"error\":{}", json_string(&e)),
+                None => String::new(),
+            };
+
+            println!(
+                "{{\"status\":\"{}\",\"output\":[{}]{}}}",
+                status.name(),
+                values.join(","),
+                error
+            );
+        }
+
+        io::stdout().flush().unwrap_or(());
+    }
+}
+
+fn run(
+    machine: &mut Machine,
+    input: &mut Option<StdinInput>,
+    printer: &mut Printer,
+    tracer: &mut Option<TraceWriter<io::Stderr>>,
+    max_steps: Option<u64>,
+) -> (Status, Option<String>) {
+    let mut steps = 0;
+
+    loop {
+        if max_steps.is_some_and(|m| steps >= m) {
+            break (Status::StepLimit, None);
+        }
+
+        let result = match tracer {
+            Some(t) => machine.step_traced(t),
+            None => machine.step(),
+        };
+
+        for value in machine.take_output() {
+            printer.output(value);
+        }
+
+        match result {
+            Ok(ComputeResult::CanContinue) => steps += 1,
+            Ok(ComputeResult::WaitingForInput) => {
+                //a prompt may not end with a newline
+                io::stdout().flush().unwrap_or(());
+
+                match input.as_mut().and_then(|i| i.input()) {
+                    Some(value) => machine.push_input(value),
+                    None => break (Status::WaitingForInput, None),
+                }
+            }
+            Ok(ComputeResult::Halt) => break (Status::Halted, None),
+            Err(e) => break (Status::Failed, Some(e.to_string())),
+        }
+    }
+}
+
+fn load(options: &Options) -> Result<Machine, String> {
+    let program = match options.program.as_deref() {
+        Some(path) if path != "-" => {
+            std::fs::read(path).map_err(|e| format!("can't read {}: {}", path, e))?
+        }
+        _ => {
+            let mut program = vec![];
+            io::stdin()
+                .read_to_end(&mut program)
+                .map_err(|e| format!("can't read the program: {}", e))?;
+            program
+        }
+    };
+
+    let intcode = if program::is_binary(&program) {
+        program::decode(&program).map_err(|e| e.to_string())?
+    } else {
+        let text = String::from_utf8(program).map_err(|_| "the program isn't text")?;
+        str_to_intcode(&text).map_err(|e| e.to_string())?
+    };
+
+    let mut machine = Machine::new(intcode);
+    machine.extend_input(options.input.iter().copied());
+
+    if let Some(path) = &options.input_file {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+
+        if options.ascii {
+            machine.push_ascii(&text).map_err(|e| e.to_string())?;
+        } else {
+            machine.extend_input(numbers(&text)?);
+        }
+    }
+
+    Ok(machine)
+}
+
+fn dump(machine: &Machine, path: &str) -> io::Result<()> {
+    let cells: Vec<String> = machine
+        .memory()
+        .to_vec()
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+
+    let memory = cells.join(",");
+
+    if path == "-" {
+        eprintln!("{}", memory);
+        Ok(())
+    } else {
+        std::fs::write(path, memory + "\n")
+    }
+}
+
+fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+
+    let options = match parse_options(&arguments) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    if options.help {
+        println!("{}", USAGE);
+        process::exit(0);
+    }
+
+    let mut machine = match load(&options) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    let program_from_stdin = matches!(options.program.as_deref(), None | Some("-"));
+    let mut input = if program_from_stdin {
+        None
+    } else {
+        Some(StdinInput {
+            ascii: options.ascii,
+            pending: VecDeque::new(),
+        })
+    };
+
+    let default_format = if options.ascii {
+        Format::Ascii
+    } else {
+        Format::Numeric
+    };
+    let mut printer = Printer {
+        format: options.format.unwrap_or(default_format),
+        values: vec![],
+    };
+
+    let mut tracer = if options.trace {
+        Some(TraceWriter::new(io::stderr()))
+    } else {
+        None
+    };
+
+    let (status, error) = run(
+        &mut machine,
+        &mut input,
+        &mut printer,
+        &mut tracer,
+        options.max_steps,
+    );
+
+    printer.finish(status, error.clone());
+
+    if let Some(e) = error {
+        eprintln!("error: {}", e);
+    } else if status != Status::Halted {
+        eprintln!("stopped: {}", status.name());
+    }
+
+    if let Some(Err(e)) = tracer.map(TraceWriter::finish) {
+        eprintln!("can't write the trace: {}", e);
+    }
+
+    if let Some(path) = &options.dump {
+        if let Err(e) = dump(&machine, path) {
+            eprintln!("can't write the memory to {}: {}", path, e);
+        }
+    }
+
+    process::exit(status.exit_code());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{json_string, parse_options, run, Format, Printer, Status};
+    use aoc2019_rust::intcode::Machine;
+
+    fn parse(arguments: &[&str]) -> Result<crate::Options, String> {
+        let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+        parse_options(&arguments)
+    }
+
+    fn status(program: &str, max_steps: Option<u64>) -> Status {
+        let mut printer = Printer {
+            format: Format::Json,
+            values: vec![],
+        };
+        let mut machine = Machine::parse(program).unwrap();
+
+        run(&mut machine, &mut None, &mut printer, &mut None, max_steps).0
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = parse(&["-i", "1,2", "--input", "3", "-o", "json", "p.txt"]).unwrap();
+        assert_eq!(options.input, vec![1, 2, 3]);
+        assert_eq!(options.format, Some(Format::Json));
+        assert_eq!(options.program.as_deref(), Some("p.txt"));
+        assert!(!options.help);
+
+        assert!(parse(&["-a", "--help"]).unwrap().help);
+        assert_eq!(parse(&["-s"]).unwrap_err(), "-s needs a value");
+        assert_eq!(parse(&["-x"]).unwrap_err(), "unknown option '-x'");
+        assert_eq!(parse(&["a", "b"]).unwrap_err(), "unexpected argument 'b'");
+        assert!(parse(&["-o", "xml"]).is_err());
+    }
+
+    #[test]
+    fn exit_codes_tell_why_the_program_stopped() {
+        assert_eq!(status("99", None).exit_code(), 0);
+        assert_eq!(status("98", None).exit_code(), 1);
+        assert_eq!(status("3,0,99", None).exit_code(), 3);
+        assert_eq!(status("1105,1,0", Some(10)).exit_code(), 4);
+
+        assert_eq!(
+            json_string("bad \"token\"\n\tat\\ 3\u{1}"),
+            "\"bad \\\"token\\\"\\n\\tat\\\\ 3\\u0001\""
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use crate::amplifier::{best_phases, Topology};
+use crate::direction::{Direction, Turn};
+use crate::grid::{step, Grid, Position};
+use crate::hull::{HullRobot, Panel};
+use crate::intcode::{computer, ComputeResult, Machine};
+use crate::solver::{Puzzle, Solver, SolverResult};
+
+/// Every day that has a solver, in order.
+pub fn puzzles() -> Vec<Box<dyn Puzzle>> {
+    vec![
+        Box::new(Day05),
+        Box::new(Day07),
+        Box::new(Day09),
+        Box::new(Day11),
+        Box::new(Day13),
+        Box::new(Day17),
+        Box::new(Day19),
+    ]
+}
+
+fn last_output(program: &str, input: i64) -> SolverResult<String> {
+    match computer(program, vec![input])?.last() {
+        Some(v) => Ok(v.to_string()),
+        None => Err("there's no output".into()),
+    }
+}
+
+/// Sunny with a Chance of Asteroids, the diagnostic code is the last output.
+pub struct Day05;
+
+impl Solver for Day05 {
+    const DAY: u32 = 5;
+
+    type Input = String;
+
+    fn parse(&self, input: &str) -> SolverResult<String> {
+        Ok(input.to_string())
+    }
+
+    fn part_one(&self, program: &String) -> SolverResult<String> {
+        last_output(program, 1)
+    }
+
+    fn part_two(&self, program: &String) -> SolverResult<String> {
+        last_output(program, 5)
+    }
+}
+
+/// Amplification Circuit.
+pub struct Day07;
+
+fn highest_signal(machine: &Machine, phases: &[i64], feedback: bool) -> SolverResult<String> {
+    let best = best_phases(phases, |phases| {
+        if feedback {
+            Topology::feedback_loop(machine, phases, 0).run(phases.len() - 1)
+        } else {
+            Topology::series(machine, phases, 0).run(phases.len() - 1)
+        }
+    })?;
+
+    match best {
+        Some((_, signal)) => Ok(signal.to_string()),
+        None => Err("the amplifiers don't produce a signal".into()),
+    }
+}
+
+impl Solver for Day07 {
+    const DAY: u32 = 7;
+
+    type Input = Machine;
+
+    fn parse(&self, input: &str) -> SolverResult<Machine> {
+        Ok(Machine::parse(input)?)
+    }
+
+    fn part_one(&self, machine: &Machine) -> SolverResult<String> {
+        highest_signal(machine, &[0, 1, 2, 3, 4], false)
+    }
+
+    fn part_two(&self, machine: &Machine) -> SolverResult<String> {
+        highest_signal(machine, &[5, 6, 7, 8, 9], true)
+    }
+}
+
+/// Sensor Boost, the BOOST program in test and in sensor boost mode.
+pub struct Day09;
+
+impl Solver for Day09 {
+    const DAY: u32 = 9;
+
+    type Input = String;
+
+    fn parse(&self, input: &str) -> SolverResult<String> {
+        Ok(input.to_string())
+    }
+
+    fn part_one(&self, program: &String) -> SolverResult<String> {
+        last_output(program, 1)
+    }
+
+    fn part_two(&self, program: &String) -> SolverResult<String> {
+        last_output(program, 2)
+    }
+}
+
+/// Space Police, a robot that paints the hull.
+pub struct Day11;
+
+impl Solver for Day11 {
+    const DAY: u32 = 11;
+
+    type Input = Machine;
+
+    fn parse(&self, input: &str) -> SolverResult<Machine> {
+        Ok(Machine::parse(input)?)
+    }
+
+    fn part_one(&self, brain: &Machine) -> SolverResult<String> {
+        let hull = HullRobot::new(brain.clone(), Panel::Black).run()?;
+        Ok(hull.painted.to_string())
+    }
+
+    fn part_two(&self, brain: &Machine) -> SolverResult<String> {
+        //starting on a white panel paints the registration identifier
+        let hull = HullRobot::new(brain.clone(), Panel::White).run()?;
+        Ok(hull.letters()?)
+    }
+}
+
+/// Care Package, an arcade game that draws tiles as (x, y, tile id).
+pub struct Day13;
+
+const BLOCK: i64 = 2;
+const PADDLE: i64 = 3;
+const BALL: i64 = 4;
+
+impl Solver for Day13 {
+    const DAY: u32 = 13;
+
+    type Input = Machine;
+
+    fn parse(&self, input: &str) -> SolverResult<Machine> {
+        Ok(Machine::parse(input)?)
+    }
+
+    fn part_one(&self, machine: &Machine) -> SolverResult<String> {
+        let mut arcade = machine.clone();
+        arcade.run_to_halt()?;
+
+        let output = arcade.take_output();
+        let blocks = output.chunks(3).filter(|t| t.get(2) == Some(&BLOCK));
+        Ok(blocks.count().to_string())
+    }
+
+    fn part_two(&self, machine: &Machine) -> SolverResult<String> {
+        let mut arcade = machine.clone();
+        //play for free
+        arcade.set_memory(0, 2)?;
+
+        let (mut ball, mut paddle, mut score) = (0, 0, 0);
+
+        loop {
+            let result = arcade.run_until_input()?;
+
+            for tile in arcade.take_output().chunks(3) {
+                match tile {
+                    [-1, 0, s] => score = *s,
+                    [x, _, BALL] => ball = *x,
+                    [x, _, PADDLE] => paddle = *x,
+                    _ => {}
+                }
+            }
+
+            if result == ComputeResult::Halt {
+                break Ok(score.to_string());
+            }
+
+            //keep the paddle under the ball
+            arcade.push_input((ball - paddle).signum());
+        }
+    }
+}
+
+/// Set and Forget, a camera that shows the scaffold and a vacuum robot on it.
+pub struct Day17;
+
+const MAX_ROUTINE: usize = 20;
+
+fn is_scaffold(scaffold: &Grid<char>, position: Position, offset: (i64, i64)) -> bool {
+    step(position, offset)
+        .ok()
+        .and_then(|p| scaffold.get(p))
+        .is_some_and(|c| *c != '.')
+}
+
+//the turns and moves that follow the scaffold from the robot to its end
+fn path(scaffold: &Grid<char>) -> Option<Vec<String>> {
+    let (mut robot, mut direction) = scaffold
+        .iter()
+        .find_map(|(p, c)| Direction::from_char(*c).map(|d| (p, d)))?;
+
+    let mut path = vec![];
+
+    loop {
+        let turns = [(Turn::Left, "L"), (Turn::Right, "R")];
+        let (turn, name) = match turns
+            .iter()
+            .find(|(t, _)| is_scaffold(scaffold, robot, direction.turn(*t).offset()))
+        {
+            Some(found) => *found,
+            None => break Some(path),
+        };
+        direction = direction.turn(turn);
+
+        let mut steps = 0;
+        while is_scaffold(scaffold, robot, direction.offset()) {
+            robot = direction.step(robot).ok()?;
+            steps += 1;
+        }
+
+        path.push(format!("{},{}", name, steps));
+    }
+}
+
+//splits the path into at most three functions and a main routine that calls them
+fn compress<'a>(
+    path: &'a [String],
+    functions: &mut Vec<&'a [String]>,
+    main: &mut Vec<usize>,
+) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+    //every call takes two characters, including the comma
+    if main.len() * 2 >= MAX_ROUTINE {
+        return false;
+    }
+
+    for i in 0..functions.len() {
+        if path.starts_with(functions[i]) {
+            main.push(i);
+            if compress(&path[functions[i].len()..], functions, main) {
+                return true;
+            }
+            main.pop();
+        }
+    }
+
+    if functions.len() < 3 {
+        for length in 1..=path.len() {
+            if path[..length].join(",").len() > MAX_ROUTINE {
+                break;
+            }
+
+            functions.push(&path[..length]);
+            main.push(functions.len() - 1);
+            if compress(&path[length..], functions, main) {
+                return true;
+            }
+            main.pop();
+            functions.pop();
+        }
+    }
+
+    false
+}
+
+impl Solver for Day17 {
+    const DAY: u32 = 17;
+
+    type Input = (Machine, Grid<char>);
+
+    fn parse(&self, input: &str) -> SolverResult<(Machine, Grid<char>)> {
+        let machine = Machine::parse(input)?;
+
+        let mut camera = machine.clone();
+        camera.run_to_halt()?;
+        let scaffold = Grid::from_ascii(&camera.take_output())?;
+
+        Ok((machine, scaffold))
+    }
+
+    fn part_one(&self, (_, scaffold): &(Machine, Grid<char>)) -> SolverResult<String> {
+        let alignment: i32 = scaffold
+            .iter()
+            .filter(|(p, _)| {
+                [(0, 0), (0, -1), (0, 1), (-1, 0), (1, 0)]
+                    .iter()
+                    .all(|offset| is_scaffold(scaffold, *p, *offset))
+            })
+            .map(|(p, _)| p.x * p.y)
+            .sum();
+
+        Ok(alignment.to_string())
+    }
+
+    fn part_two(&self, (machine, scaffold): &(Machine, Grid<char>)) -> SolverResult<String> {
+        let path = path(scaffold).ok_or("there's no robot on the scaffold")?;
+
+        let (mut functions, mut main) = (vec![], vec![]);
+        if !compress(&path, &mut functions, &mut main) {
+            return Err("the path doesn't fit in the movement functions".into());
+        }
+
+        let mut robot = machine.clone();
+        //wake the robot up
+        robot.set_memory(0, 2)?;
+
+        let main: Vec<String> = main
+            .iter()
+            .map(|&f| ((b'A' + f as u8) as char).to_string())
+            .collect();
+        robot.push_line(&main.join(","))?;
+        for f in 0..3 {
+            robot.push_line(&functions.get(f).map_or(String::new(), |f| f.join(",")))?;
+        }
+        //no continuous video feed
+        robot.push_line("n")?;
+
+        robot.run_to_halt()?;
+
+        match robot.take_ascii_output().answers.last() {
+            Some(dust) => Ok(dust.to_string()),
+            None => Err("the robot didn't report the dust it collected".into()),
+        }
+    }
+}
+
+/// Tractor Beam, a drone program that reports whether a point is in the beam.
+pub struct Day19;
+
+const SHIP: i64 = 100;
+//the rows searched for the ship, a beam that fits it at all does so well before
+const BEAM_ROWS: i64 = 10_000;
+
+fn pulled(drone: &Machine, x: i64, y: i64) -> SolverResult<bool> {
+    let mut drone = drone.clone();
+    drone.extend_input(vec![x, y]);
+    drone.run_to_halt()?;
+    Ok(drone.output() == [1])
+}
+
+impl Solver for Day19 {
+    const DAY: u32 = 19;
+
+    type Input = Machine;
+
+    fn parse(&self, input: &str) -> SolverResult<Machine> {
+        Ok(Machine::parse(input)?)
+    }
+
+    fn part_one(&self, drone: &Machine) -> SolverResult<String> {
+        let mut points = 0;
+
+        for x in 0..50 {
+            for y in 0..50 {
+                if pulled(drone, x, y)? {
+                    points += 1;
+                }
+            }
+        }
+
+        Ok(points.to_string())
+    }
+
+    fn part_two(&self, drone: &Machine) -> SolverResult<String> {
+        //follow the lower edge of the beam until the ship fits above and to the right of it
+        let mut x = 0;
+
+        for y in SHIP..BEAM_ROWS {
+            let start = x;
+            while !pulled(drone, x, y)? {
+                x += 1;
+                //the beam may have gaps in the rows close to the emitter
+                if x > start + 10 * SHIP {
+                    x = start;
+                    break;
+                }
+            }
+
+            if pulled(drone, x, y)? && pulled(drone, x + SHIP - 1, y - SHIP + 1)? {
+                return Ok((x * 10000 + y - SHIP + 1).to_string());
+            }
+        }
+
+        Err(format!(
+            "the ship doesn't fit in the first {} rows of the beam",
+            BEAM_ROWS
+        )
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::days::puzzles;
+    use crate::solver::{run, Check};
+
+    #[test]
+    fn every_day_gives_the_expected_answers() {
+        for puzzle in puzzles() {
+            let solution = run(puzzle.as_ref()).unwrap();
+
+            for part in solution.parts {
+                assert_eq!(part.check, Check::Correct, "day {}", solution.day);
+            }
+        }
+    }
+}
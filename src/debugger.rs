@@ -0,0 +1,344 @@
+use crate::disassemble::Instruction;
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::{DenseMemory, Memory};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+/// The most memory cells `Debugger::dump` shows at once.
+pub const MAX_DUMP: usize = 4096;
+
+/// A range of memory that can't be dumped, because it runs past the highest
+/// address or is longer than `MAX_DUMP` cells.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct InvalidRange {
+    pub address: usize,
+    pub count: usize,
+}
+
+impl fmt::Display for InvalidRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count > MAX_DUMP {
+            write!(f, "can't dump more than {} cells at once", MAX_DUMP)
+        } else {
+            write!(
+                f,
+                "{} cells from address {} run past the highest address",
+                self.count, self.address
+            )
+        }
+    }
+}
+
+impl Error for InvalidRange {}
+
+/// Why the debugger handed control back.
+#[derive(Clone, PartialEq, Debug)]
+pub enum StopReason {
+    /// A single instruction was executed.
+    Stepped,
+    Breakpoint(usize),
+    Watchpoint {
+        address: usize,
+        old: i64,
+        new: i64,
+    },
+    Halt,
+    WaitingForInput,
+}
+
+/// Runs a machine under control, stopping on breakpoints (addresses of
+/// instructions) and watchpoints (memory cells whose value changes).
+pub struct Debugger<M: Memory = DenseMemory> {
+    machine: Machine<M>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
+    //address of the last executed instruction, used to recognize calls
+    previous: Option<usize>,
+}
+
+impl<M: Memory> Debugger<M> {
+    pub fn new(machine: Machine<M>) -> Debugger<M> {
+        Debugger {
+            machine,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            previous: None,
+        }
+    }
+
+    pub fn machine(&self) -> &Machine<M> {
+        &self.machine
+    }
+
+    /// Gives access to the machine, to edit memory, registers or input mid-run.
+    pub fn machine_mut(&mut self) -> &mut Machine<M> {
+        &mut self.machine
+    }
+
+    pub fn into_machine(self) -> Machine<M> {
+        self.machine
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    pub fn breakpoints(&self) -> &BTreeSet<usize> {
+        &self.breakpoints
+    }
+
+    pub fn add_watchpoint(&mut self, address: usize) {
+        self.watchpoints.insert(address);
+    }
+
+    pub fn remove_watchpoint(&mut self, address: usize) -> bool {
+        self.watchpoints.remove(&address)
+    }
+
+    pub fn watchpoints(&self) -> &BTreeSet<usize> {
+        &self.watchpoints
+    }
+
+    fn peek(&self, address: usize) -> i64 {
+        self.machine.memory().read(address)
+    }
+
+    fn instruction_pointer(&self) -> usize {
+        self.machine.instruction_pointer() as usize
+    }
+
+    /// The instruction the machine is about to execute.
+    pub fn current_instruction(&self) -> Option<Instruction> {
+        Instruction::decode_from(self.machine.memory(), self.instruction_pointer())
+    }
+
+    /// Executes one instruction, reporting a watchpoint if it changed a watched cell.
+    pub fn step(&mut self) -> Result<StopReason, IntcodeError> {
+        let watched: Vec<(usize, i64)> = self
+            .watchpoints
+            .iter()
+            .map(|&address| (address, self.peek(address)))
+            .collect();
+
+        let address = self.instruction_pointer();
+
+        match self.machine.step()? {
+            ComputeResult::Halt => return Ok(StopReason::Halt),
+            ComputeResult::WaitingForInput => return Ok(StopReason::WaitingForInput),
+            ComputeResult::CanContinue => self.previous = Some(address),
+        }
+
+        let changed = watched
+            .into_iter()
+            .map(|(address, old)| (address, old, self.peek(address)))
+            .find(|(_, old, new)| old != new);
+
+        match changed {
+            Some((address, old, new)) => Ok(StopReason::Watchpoint { address, old, new }),
+            None => Ok(StopReason::Stepped),
+        }
+    }
+
+    /// Runs until a breakpoint or watchpoint is hit, or the machine halts or
+    /// needs input. A breakpoint at the current instruction doesn't stop it.
+    pub fn resume(&mut self) -> Result<StopReason, IntcodeError> {
+        self.run_until(|_| false)
+    }
+
+    fn run_until<F: Fn(&Debugger<M>) -> bool>(
+        &mut self,
+        done: F,
+    ) -> Result<StopReason, IntcodeError> {
+        loop {
+            match self.step()? {
+                StopReason::Stepped => {
+                    let address = self.instruction_pointer();
+
+                    if done(self) {
+                        break Ok(StopReason::Stepped);
+                    } else if self.breakpoints.contains(&address) {
+                        break Ok(StopReason::Breakpoint(address));
+                    }
+                }
+                reason => break Ok(reason),
+            }
+        }
+    }
+
+    /// Like `step`, but runs a whole function when the current instruction calls one.
+    ///
+    /// A call is a return address stored by an `add`/`mul` of two immediates,
+    /// followed by an unconditional jump. The call returns when execution
+    /// reaches the return address with the relative base of the caller.
+    pub fn step_over(&mut self) -> Result<StopReason, IntcodeError> {
+        match self.return_address() {
+            Some(return_address) => {
+                let relative_base = self.machine.relative_base();
+
+                self.run_until(|d| {
+                    d.instruction_pointer() == return_address
+                        && d.machine.relative_base() == relative_base
+                })
+            }
+            None => self.step(),
+        }
+    }
+
+    fn return_address(&self) -> Option<usize> {
+        let is_call = |store: &Instruction, jump: &Instruction| {
+            let end = jump.address + jump.size();
+
+            store.constant_result() == Some(end as i64) && jump.is_unconditional_jump()
+        };
+
+        let current = self.current_instruction()?;
+
+        //about to store the return address
+        if let Some(jump) =
+            Instruction::decode_from(self.machine.memory(), current.address + current.size())
+        {
+            if is_call(&current, &jump) {
+                return Some(jump.address + jump.size());
+            }
+        }
+
+        //about to jump, having just stored the return address
+        let previous = Instruction::decode_from(self.machine.memory(), self.previous?)?;
+
+        if previous.address + previous.size() == current.address && is_call(&previous, &current) {
+            Some(current.address + current.size())
+        } else {
+            None
+        }
+    }
+
+    /// Shows `count` memory cells starting at `address`, eight per row.
+    pub fn dump(&self, address: usize, count: usize) -> Result<String, InvalidRange> {
+        let end = match address.checked_add(count) {
+            Some(end) if count <= MAX_DUMP => end,
+            _ => return Err(InvalidRange { address, count }),
+        };
+
+        let rows = (address..end)
+            .collect::<Vec<usize>>()
+            .chunks(8)
+            .map(|row| {
+                let values: Vec<String> = row
+                    .iter()
+                    .map(|&a| format!("{:>8}", self.peek(a)))
+                    .collect();
+
+                format!("{:>6}:{}\n", row[0], values.join(""))
+            })
+            .collect();
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assemble::assemble;
+    use crate::debugger::{Debugger, StopReason, MAX_DUMP};
+    use crate::intcode::Machine;
+
+    fn debugger(source: &str) -> Debugger {
+        Debugger::new(Machine::new(assemble(source).unwrap()))
+    }
+
+    #[test]
+    fn stops_at_breakpoints() {
+        let mut debugger = debugger(
+            "
+            add #1, #2, [20]
+            out [20]
+            hlt",
+        );
+
+        debugger.add_breakpoint(4);
+
+        assert_eq!(debugger.resume(), Ok(StopReason::Breakpoint(4)));
+        assert_eq!(debugger.machine().output(), &[] as &[i64]);
+        assert_eq!(debugger.current_instruction().unwrap().mnemonic, "out");
+
+        //editing memory mid-run
+        debugger.machine_mut().set_memory(20, 42).unwrap();
+
+        assert_eq!(debugger.resume(), Ok(StopReason::Halt));
+        assert_eq!(debugger.machine().output(), &[42]);
+    }
+
+    #[test]
+    fn stops_at_watchpoints() {
+        let mut debugger = debugger(
+            "
+            loop:   add [counter], #1, [counter]
+                    eq [counter], #3, [done]
+                    jf [done], #loop
+                    hlt
+            counter: dw 0
+            done:    dw 0",
+        );
+
+        debugger.add_watchpoint(12);
+
+        assert_eq!(
+            debugger.resume(),
+            Ok(StopReason::Watchpoint {
+                address: 12,
+                old: 0,
+                new: 1
+            })
+        );
+        assert_eq!(
+            debugger.resume().unwrap(),
+            StopReason::Watchpoint {
+                address: 12,
+                old: 1,
+                new: 2
+            }
+        );
+        assert_eq!(
+            debugger.dump(12, 4),
+            Ok("    12:       2       0       0       0\n".to_string())
+        );
+        assert!(debugger.dump(usize::MAX, 8).is_err());
+        assert!(debugger.dump(0, MAX_DUMP + 1).is_err());
+    }
+
+    #[test]
+    fn steps_over_calls() {
+        let mut debugger = debugger(
+            "
+                    arb #100
+                    add #back, #0, rb+0
+                    jt #1, #function
+            back:   out #2
+                    hlt
+            function:
+                    out #1
+                    jf #0, rb+0",
+        );
+
+        assert_eq!(debugger.step(), Ok(StopReason::Stepped));
+        //store the return address and jump
+        assert_eq!(debugger.step_over(), Ok(StopReason::Stepped));
+        assert_eq!(debugger.machine().instruction_pointer(), 9);
+        assert_eq!(debugger.machine().output(), &[1]);
+
+        //or just the jump
+        debugger.machine_mut().set_instruction_pointer(2);
+        assert_eq!(debugger.step(), Ok(StopReason::Stepped));
+        assert_eq!(debugger.step_over(), Ok(StopReason::Stepped));
+        assert_eq!(debugger.machine().instruction_pointer(), 9);
+
+        assert_eq!(debugger.step_over(), Ok(StopReason::Stepped));
+        assert_eq!(debugger.step_over(), Ok(StopReason::Halt));
+        assert_eq!(debugger.machine().output(), &[1, 1, 2]);
+    }
+}
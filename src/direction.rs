@@ -0,0 +1,303 @@
+use crate::grid::{step, Coordinate, CoordinateOverflow, Position};
+use std::fmt;
+
+/// One of the four directions on a grid where y grows down.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+/// A change of direction.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Turn {
+    Left,
+    Right,
+    Around,
+}
+
+impl Turn {
+    /// The turn a painting robot outputs, 0 for left and 1 for right.
+    pub fn from_code(code: i64) -> Option<Turn> {
+        match code {
+            0 => Some(Turn::Left),
+            1 => Some(Turn::Right),
+            _ => None,
+        }
+    }
+
+    //quarter turns clockwise
+    fn quarters(self) -> usize {
+        match self {
+            Turn::Right => 1,
+            Turn::Around => 2,
+            Turn::Left => 3,
+        }
+    }
+}
+
+impl Direction {
+    /// Clockwise from up.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn turn(self, turn: Turn) -> Direction {
+        Direction::ALL[(self.index() + turn.quarters()) % 4]
+    }
+
+    pub fn left(self) -> Direction {
+        self.turn(Turn::Left)
+    }
+
+    pub fn right(self) -> Direction {
+        self.turn(Turn::Right)
+    }
+
+    pub fn reverse(self) -> Direction {
+        self.turn(Turn::Around)
+    }
+
+    /// The turn that changes this direction into the other one, `None` if they're the same.
+    pub fn turn_to(self, other: Direction) -> Option<Turn> {
+        match (other.index() + 4 - self.index()) % 4 {
+            1 => Some(Turn::Right),
+            2 => Some(Turn::Around),
+            3 => Some(Turn::Left),
+            _ => None,
+        }
+    }
+
+    /// The offset of one step, as (dx, dy).
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    /// One step from the position, or an error if it leaves the range of the coordinates.
+    pub fn step<N: Coordinate>(
+        self,
+        position: Position<N>,
+    ) -> Result<Position<N>, CoordinateOverflow> {
+        step(position, self.offset())
+    }
+
+    /// The movement commands of the repair droid, 1 north, 2 south, 3 west and 4 east.
+    pub fn from_code(code: i64) -> Option<Direction> {
+        match code {
+            1 => Some(Direction::Up),
+            2 => Some(Direction::Down),
+            3 => Some(Direction::Left),
+            4 => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i64 {
+        match self {
+            Direction::Up => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+            Direction::Right => 4,
+        }
+    }
+
+    /// A robot drawn by an ASCII camera as `^`, `>`, `v` or `<`, or one of the `Display` arrows.
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            '^' | '↑' => Some(Direction::Up),
+            '>' | '→' => Some(Direction::Right),
+            'v' | '↓' => Some(Direction::Down),
+            '<' | '←' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = match self {
+            Direction::Up => '↑',
+            Direction::Right => '→',
+            Direction::Down => '↓',
+            Direction::Left => '←',
+        };
+
+        write!(f, "{}", arrow)
+    }
+}
+
+/// One of the eight directions, including the diagonal ones.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    /// Clockwise from up.
+    pub const ALL: [Direction8; 8] = [
+        Direction8::Up,
+        Direction8::UpRight,
+        Direction8::Right,
+        Direction8::DownRight,
+        Direction8::Down,
+        Direction8::DownLeft,
+        Direction8::Left,
+        Direction8::UpLeft,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn turn(self, turn: Turn) -> Direction8 {
+        Direction8::ALL[(self.index() + turn.quarters() * 2) % 8]
+    }
+
+    /// An eighth of a turn clockwise.
+    pub fn clockwise(self) -> Direction8 {
+        Direction8::ALL[(self.index() + 1) % 8]
+    }
+
+    /// An eighth of a turn counterclockwise.
+    pub fn counterclockwise(self) -> Direction8 {
+        Direction8::ALL[(self.index() + 7) % 8]
+    }
+
+    pub fn reverse(self) -> Direction8 {
+        self.turn(Turn::Around)
+    }
+
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Direction8::Up => (0, -1),
+            Direction8::UpRight => (1, -1),
+            Direction8::Right => (1, 0),
+            Direction8::DownRight => (1, 1),
+            Direction8::Down => (0, 1),
+            Direction8::DownLeft => (-1, 1),
+            Direction8::Left => (-1, 0),
+            Direction8::UpLeft => (-1, -1),
+        }
+    }
+
+    pub fn step<N: Coordinate>(
+        self,
+        position: Position<N>,
+    ) -> Result<Position<N>, CoordinateOverflow> {
+        step(position, self.offset())
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Direction8 {
+        Direction8::ALL[direction.index() * 2]
+    }
+}
+
+impl fmt::Display for Direction8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = match self {
+            Direction8::Up => '↑',
+            Direction8::UpRight => '↗',
+            Direction8::Right => '→',
+            Direction8::DownRight => '↘',
+            Direction8::Down => '↓',
+            Direction8::DownLeft => '↙',
+            Direction8::Left => '←',
+            Direction8::UpLeft => '↖',
+        };
+
+        write!(f, "{}", arrow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::{Direction, Direction8, Turn};
+    use crate::grid::Position;
+
+    const TURNS: [Turn; 3] = [Turn::Left, Turn::Right, Turn::Around];
+
+    #[test]
+    fn turns_behave_like_rotations() {
+        for d in Direction::ALL {
+            assert_eq!(d.left().left().left().left(), d);
+            assert_eq!(d.right().right().right().right(), d);
+            assert_eq!(d.left().right(), d);
+            assert_eq!(d.reverse().reverse(), d);
+            assert_eq!(d.right().right(), d.reverse());
+            assert_ne!(d.reverse(), d);
+            assert_eq!(d.turn_to(d), None);
+
+            for t in TURNS {
+                assert_eq!(d.turn_to(d.turn(t)), Some(t));
+                assert_eq!(
+                    Direction8::from(d).turn(t),
+                    Direction8::from(d.turn(t)),
+                    "the 4 and 8 way turns agree"
+                );
+            }
+
+            //a step there and back again
+            let p = Position::new(3, -7);
+            assert_eq!(d.reverse().step(d.step(p).unwrap()), Ok(p));
+            let (dx, dy) = d.offset();
+            let (rx, ry) = d.right().offset();
+            assert_eq!(dx * rx + dy * ry, 0, "a right turn is perpendicular");
+        }
+
+        for d in Direction8::ALL {
+            let mut clockwise = d;
+            for _ in 0..8 {
+                clockwise = clockwise.clockwise();
+            }
+            assert_eq!(clockwise, d);
+            assert_eq!(d.clockwise().counterclockwise(), d);
+            assert_eq!(d.clockwise().clockwise(), d.turn(Turn::Right));
+
+            let ((dx, dy), (rx, ry)) = (d.offset(), d.reverse().offset());
+            assert_eq!((dx + rx, dy + ry), (0, 0));
+        }
+    }
+
+    #[test]
+    fn parses_codes_and_characters() {
+        for d in Direction::ALL {
+            assert_eq!(Direction::from_code(d.code()), Some(d));
+            assert_eq!(
+                Direction::from_char(d.to_string().chars().next().unwrap()),
+                Some(d)
+            );
+        }
+
+        assert_eq!(Direction::from_char('v'), Some(Direction::Down));
+        assert_eq!(Direction::from_char('#'), None);
+        assert_eq!(Direction::from_code(5), None);
+        assert_eq!(Turn::from_code(0), Some(Turn::Left));
+        assert_eq!(Turn::from_code(2), None);
+        assert_eq!(Direction8::DownLeft.to_string(), "↙");
+
+        assert!(Direction::Left.step(Position::<i8>::new(-128, 0)).is_err());
+    }
+}
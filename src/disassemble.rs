@@ -0,0 +1,323 @@
+use crate::intcode::parameter_modes;
+use crate::memory::Memory;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+
+/// An instruction parameter together with its addressing mode.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Operand {
+    Position(i64),
+    Immediate(i64),
+    Relative(i64),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Position(address) => write!(f, "[{}]", address),
+            Operand::Immediate(value) => write!(f, "#{}", value),
+            Operand::Relative(offset) if *offset < 0 => write!(f, "rb{}", offset),
+            Operand::Relative(offset) => write!(f, "rb+{}", offset),
+        }
+    }
+}
+
+/// A decoded instruction at a known address.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Instruction {
+    pub address: usize,
+    pub opcode: i64,
+    pub mnemonic: &'static str,
+    pub operands: Vec<Operand>,
+}
+
+impl Instruction {
+    /// Decodes the instruction at `address`, or returns `None` when the value
+    /// there isn't a valid opcode with valid parameter modes.
+    pub fn decode(intcode: &[i64], address: usize) -> Option<Instruction> {
+        Instruction::decode_with(|a| intcode.get(a).copied(), address)
+    }
+
+    /// Like `decode`, but reads the instruction from the memory of a machine.
+    pub fn decode_from<M: Memory>(memory: &M, address: usize) -> Option<Instruction> {
+        Instruction::decode_with(
+            |a| Some(memory.read(a)).filter(|_| a < memory.size()),
+            address,
+        )
+    }
+
+    fn decode_with<F: Fn(usize) -> Option<i64>>(cell: F, address: usize) -> Option<Instruction> {
+        let opcode = cell(address)?;
+        let (a, b, c, op) = parameter_modes(opcode);
+
+        //the mnemonic, the number of parameters and which of them is written to
+        let (mnemonic, parameters, writes): (_, usize, Option<usize>) = match op {
+            1 => ("add", 3, Some(2)),
+            2 => ("mul", 3, Some(2)),
+            3 => ("in", 1, Some(0)),
+            4 => ("out", 1, None),
+            5 => ("jt", 2, None),
+            6 => ("jf", 2, None),
+            7 => ("lt", 3, Some(2)),
+            8 => ("eq", 3, Some(2)),
+            9 => ("arb", 1, None),
+            99 => ("hlt", 0, None),
+            _ => return None,
+        };
+
+        let operands = [c, b, a]
+            .iter()
+            .take(parameters)
+            .enumerate()
+            .map(|(i, mode)| {
+                let value = cell(address + 1 + i)?;
+
+                match mode {
+                    0 => Some(Operand::Position(value)),
+                    1 if writes != Some(i) => Some(Operand::Immediate(value)),
+                    2 => Some(Operand::Relative(value)),
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<Operand>>>()?;
+
+        Some(Instruction {
+            address,
+            opcode,
+            mnemonic,
+            operands,
+        })
+    }
+
+    /// The number of memory cells the instruction occupies.
+    pub fn size(&self) -> usize {
+        self.operands.len() + 1
+    }
+
+    /// The target of a jump, when it is known without running the program.
+    pub fn jump_target(&self) -> Option<usize> {
+        match (self.mnemonic, self.operands.get(1)) {
+            ("jt", Some(Operand::Immediate(target))) | ("jf", Some(Operand::Immediate(target))) => {
+                Some(*target as usize).filter(|_| *target >= 0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether execution can continue with the next instruction.
+    fn falls_through(&self) -> bool {
+        match (self.mnemonic, self.operands[..].first()) {
+            ("hlt", _) => false,
+            ("jt", Some(Operand::Immediate(v))) => *v == 0,
+            ("jf", Some(Operand::Immediate(v))) => *v != 0,
+            _ => true,
+        }
+    }
+
+    pub(crate) fn is_unconditional_jump(&self) -> bool {
+        self.jump_target().is_some() && !self.falls_through()
+    }
+
+    /// The value an `add` or `mul` of two immediates stores, which is how
+    /// programs push a return address before calling a function.
+    pub(crate) fn constant_result(&self) -> Option<i64> {
+        match (self.mnemonic, &self.operands[..]) {
+            ("add", [Operand::Immediate(x), Operand::Immediate(y), _]) => x.checked_add(*y),
+            ("mul", [Operand::Immediate(x), Operand::Immediate(y), _]) => x.checked_mul(*y),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operands: Vec<String> = self.operands.iter().map(|o| o.to_string()).collect();
+
+        if operands.is_empty() {
+            write!(f, "{}", self.mnemonic)
+        } else {
+            write!(f, "{:<4} {}", self.mnemonic, operands.join(", "))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Entry {
+    Code(Instruction),
+    Data { address: usize, value: i64 },
+}
+
+/// An annotated listing of a whole program. Code is whatever is reachable from
+/// address 0, everything else is shown as data.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Listing {
+    pub entries: Vec<Entry>,
+    /// Addresses that are the target of a jump or a return address.
+    pub labels: BTreeSet<usize>,
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let address = match entry {
+                Entry::Code(instruction) => instruction.address,
+                Entry::Data { address, .. } => *address,
+            };
+
+            if self.labels.contains(&address) {
+                writeln!(f, "L{}:", address)?;
+            }
+
+            match entry {
+                Entry::Code(instruction) => writeln!(f, "{:>6}: {}", address, instruction)?,
+                Entry::Data { value, .. } => writeln!(f, "{:>6}: data {}", address, value)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Disassembles a program, following every statically known jump from address 0.
+pub fn disassemble(intcode: &[i64]) -> Listing {
+    let mut code = BTreeSet::new();
+    let mut labels = BTreeSet::new();
+    let mut to_visit: VecDeque<usize> = VecDeque::new();
+
+    to_visit.push_back(0);
+
+    while let Some(address) = to_visit.pop_front() {
+        if code.contains(&address) {
+            continue;
+        }
+
+        let instruction = match Instruction::decode(intcode, address) {
+            Some(i) => i,
+            //reachable, but not a valid instruction, so it's shown as data
+            None => continue,
+        };
+
+        code.insert(address);
+
+        if let Some(target) = instruction.jump_target() {
+            labels.insert(target);
+            to_visit.push_back(target);
+        }
+
+        if instruction.falls_through() {
+            let next = address + instruction.size();
+
+            //an immediate value stored right before an unconditional jump is a return address
+            if let (Some(value), Some(jump)) = (
+                instruction.constant_result(),
+                Instruction::decode(intcode, next),
+            ) {
+                if jump.is_unconditional_jump() && value >= 0 && (value as usize) < intcode.len() {
+                    labels.insert(value as usize);
+                    to_visit.push_back(value as usize);
+                }
+            }
+
+            to_visit.push_back(next);
+        }
+    }
+
+    let mut entries = vec![];
+    let mut address = 0;
+
+    while address < intcode.len() {
+        match Instruction::decode(intcode, address).filter(|_| code.contains(&address)) {
+            Some(instruction) => {
+                address += instruction.size();
+                entries.push(Entry::Code(instruction));
+            }
+            None => {
+                entries.push(Entry::Data {
+                    address,
+                    value: intcode[address],
+                });
+                address += 1;
+            }
+        }
+    }
+
+    Listing { entries, labels }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disassemble::{disassemble, Entry, Instruction, Operand};
+
+    #[test]
+    fn decodes_modes() {
+        let instruction = Instruction::decode(&[21001, 3, -2, 4], 0).unwrap();
+
+        assert_eq!(instruction.mnemonic, "add");
+        assert_eq!(
+            instruction.operands,
+            vec![
+                Operand::Position(3),
+                Operand::Immediate(-2),
+                Operand::Relative(4)
+            ]
+        );
+        assert_eq!(instruction.to_string(), "add  [3], #-2, rb+4");
+    }
+
+    #[test]
+    fn rejects_invalid_instructions() {
+        assert_eq!(Instruction::decode(&[42], 0), None);
+        assert_eq!(Instruction::decode(&[301, 0, 0, 0], 0), None);
+        //writing in immediate mode
+        assert_eq!(Instruction::decode(&[11101, 0, 0, 0], 0), None);
+        //missing parameters
+        assert_eq!(Instruction::decode(&[1, 0], 0), None);
+    }
+
+    #[test]
+    fn splits_code_and_data() {
+        let listing = disassemble(&[1002, 4, 3, 4, 33]);
+
+        assert_eq!(
+            listing.entries[1],
+            Entry::Data {
+                address: 4,
+                value: 33
+            }
+        );
+        assert_eq!(
+            listing.to_string(),
+            "     0: mul  [4], #3, [4]\n     4: data 33\n"
+        );
+    }
+
+    #[test]
+    fn follows_jumps_and_calls() {
+        //jump over some data, call a function that returns through the stack and halt
+        let listing = disassemble(&[
+            1105, 1, 5, 7, 7, 109, 100, 21101, 16, 0, 0, 1105, 1, 17, 7, 7, 99, 2106, 0, 0,
+        ]);
+
+        assert_eq!(
+            listing.labels.iter().cloned().collect::<Vec<_>>(),
+            vec![5, 16, 17]
+        );
+        assert_eq!(
+            listing.to_string(),
+            "     0: jt   #1, #5
+     3: data 7
+     4: data 7
+L5:
+     5: arb  #100
+     7: add  #16, #0, rb+0
+    11: jt   #1, #17
+    14: data 7
+    15: data 7
+L16:
+    16: hlt
+L17:
+    17: jf   #0, rb+0
+"
+        );
+    }
+}
@@ -0,0 +1,506 @@
+use crate::intcode::ComputeResult::{self, CanContinue, Halt, WaitingForInput};
+use crate::intcode::{parameter_modes, str_to_intcode, IntcodeError};
+use crate::memory::DEFAULT_LIMIT;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+#[derive(Clone, Copy, Debug)]
+enum Parameter {
+    Position(i64),
+    Immediate(i64),
+    Relative(i64),
+}
+
+//parameters that are written to can't be in immediate mode
+#[derive(Clone, Copy, Debug)]
+enum Target {
+    Position(i64),
+    Relative(i64),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Add(Parameter, Parameter, Target),
+    Multiply(Parameter, Parameter, Target),
+    Input(Target),
+    Output(Parameter),
+    JumpIfTrue(Parameter, Parameter),
+    JumpIfFalse(Parameter, Parameter),
+    LessThan(Parameter, Parameter, Target),
+    Equals(Parameter, Parameter, Target),
+    AdjustRelativeBase(Parameter),
+    Halt,
+    //decoding failed, the error is reported when the instruction is executed
+    Invalid(Invalid),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Invalid {
+    UnknownOpcode,
+    ParameterMode(u8),
+    WriteInImmediateMode,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Fault {
+    Invalid(Invalid),
+    NegativeAddress(i64),
+    MemoryLimitExceeded(usize),
+    InstructionPointerOutOfRange(i64),
+}
+
+/// An Intcode interpreter that decodes every instruction once, into a cache
+/// next to the memory, instead of on every execution. Writes to memory
+/// invalidate the cached instructions they overlap, so self-modifying
+/// programs behave exactly like on `Machine`. The memory has the same
+/// limit as a `DenseMemory`.
+pub struct FastMachine {
+    instruction_pointer: u32,
+    memory: Vec<i64>,
+    decoded: Vec<Option<Op>>,
+    limit: Option<usize>,
+    input: VecDeque<i64>,
+    output: Vec<i64>,
+    relative_base: i64,
+}
+
+//the longest instruction, the opcode and three parameters
+const MAX_INSTRUCTION_SIZE: usize = 4;
+
+fn decode(memory: &[i64], address: usize) -> Op {
+    let read = |offset: usize| memory.get(address + offset).copied().unwrap_or(0);
+    let opcode = read(0);
+    let (a, b, c, op) = parameter_modes(opcode);
+
+    let parameter = |mode: u8, offset: usize| match mode {
+        0 => Ok(Parameter::Position(read(offset))),
+        1 => Ok(Parameter::Immediate(read(offset))),
+        2 => Ok(Parameter::Relative(read(offset))),
+        m => Err(Invalid::ParameterMode(m)),
+    };
+    let target = |mode: u8, offset: usize| match mode {
+        0 => Ok(Target::Position(read(offset))),
+        1 => Err(Invalid::WriteInImmediateMode),
+        2 => Ok(Target::Relative(read(offset))),
+        m => Err(Invalid::ParameterMode(m)),
+    };
+
+    let decoded = match op {
+        1 => parameter(c, 1)
+            .and_then(|x| parameter(b, 2).and_then(|y| target(a, 3).map(|t| Op::Add(x, y, t)))),
+        2 => parameter(c, 1).and_then(|x| {
+            parameter(b, 2).and_then(|y| target(a, 3).map(|t| Op::Multiply(x, y, t)))
+        }),
+        3 => target(c, 1).map(Op::Input),
+        4 => parameter(c, 1).map(Op::Output),
+        5 => parameter(c, 1).and_then(|x| parameter(b, 2).map(|y| Op::JumpIfTrue(x, y))),
+        6 => parameter(c, 1).and_then(|x| parameter(b, 2).map(|y| Op::JumpIfFalse(x, y))),
+        7 => parameter(c, 1).and_then(|x| {
+            parameter(b, 2).and_then(|y| target(a, 3).map(|t| Op::LessThan(x, y, t)))
+        }),
+        8 => parameter(c, 1)
+            .and_then(|x| parameter(b, 2).and_then(|y| target(a, 3).map(|t| Op::Equals(x, y, t)))),
+        9 => parameter(c, 1).map(Op::AdjustRelativeBase),
+        99 => Ok(Op::Halt),
+        _ => Err(Invalid::UnknownOpcode),
+    };
+
+    decoded.unwrap_or_else(Op::Invalid)
+}
+
+impl FastMachine {
+    pub fn new(intcode: Vec<i64>) -> FastMachine {
+        FastMachine {
+            instruction_pointer: 0,
+            decoded: vec![None; intcode.len()],
+            memory: intcode,
+            limit: Some(DEFAULT_LIMIT),
+            input: VecDeque::new(),
+            output: vec![],
+            relative_base: 0,
+        }
+    }
+
+    /// Creates a machine from comma-separated Intcode.
+    pub fn parse(string: &str) -> Result<FastMachine, IntcodeError> {
+        str_to_intcode(string).map(FastMachine::new)
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.input.push_back(value)
+    }
+
+    pub fn extend_input<I: IntoIterator<Item = i64>>(&mut self, values: I) {
+        self.input.extend(values)
+    }
+
+    pub fn output(&self) -> &[i64] {
+        &self.output
+    }
+
+    pub fn take_output(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.output)
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn instruction_pointer(&self) -> u32 {
+        self.instruction_pointer
+    }
+
+    /// The most cells the memory may grow to, `None` if it may grow as it likes.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Changes the limit like `Memory::set_limit`.
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    fn error(&self, fault: Fault) -> IntcodeError {
+        let instruction_pointer = self.instruction_pointer;
+        let opcode = || self.memory[instruction_pointer as usize];
+
+        match fault {
+            Fault::Invalid(Invalid::UnknownOpcode) => IntcodeError::UnknownOpcode {
+                instruction_pointer,
+                opcode: opcode(),
+            },
+            Fault::Invalid(Invalid::ParameterMode(mode)) => IntcodeError::InvalidParameterMode {
+                instruction_pointer,
+                opcode: opcode(),
+                mode,
+            },
+            Fault::Invalid(Invalid::WriteInImmediateMode) => IntcodeError::WriteInImmediateMode {
+                instruction_pointer,
+                opcode: opcode(),
+            },
+            Fault::NegativeAddress(address) => IntcodeError::NegativeAddress {
+                instruction_pointer,
+                opcode: opcode(),
+                address,
+            },
+            Fault::MemoryLimitExceeded(address) => IntcodeError::MemoryLimitExceeded {
+                instruction_pointer,
+                opcode: opcode(),
+                address,
+            },
+            Fault::InstructionPointerOutOfRange(pointer) => {
+                IntcodeError::InstructionPointerOutOfRange {
+                    instruction_pointer: pointer,
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn address(&self, address: i64) -> Result<usize, Fault> {
+        usize::try_from(address).map_err(|_| Fault::NegativeAddress(address))
+    }
+    #[inline]
+    fn value(&self, parameter: Parameter) -> Result<i64, Fault> {
+        let address = match parameter {
+            Parameter::Immediate(value) => return Ok(value),
+            Parameter::Position(address) => address,
+            Parameter::Relative(offset) => offset.wrapping_add(self.relative_base),
+        };
+
+        self.address(address)
+            .map(|a| self.memory.get(a).copied().unwrap_or(0))
+    }
+
+    #[inline]
+    fn target(&self, target: Target) -> Result<usize, Fault> {
+        match target {
+            Target::Position(address) => self.address(address),
+            Target::Relative(offset) => self.address(offset.wrapping_add(self.relative_base)),
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, address: usize, value: i64) -> Result<(), Fault> {
+        if address >= self.memory.len() {
+            if let Some(limit) = self.limit {
+                if address >= limit {
+                    return Err(Fault::MemoryLimitExceeded(address));
+                }
+            }
+
+            self.memory.resize(address + 1, 0);
+            self.decoded.resize(address + 1, None);
+        }
+
+        self.memory[address] = value;
+
+        //forget every decoded instruction that covers the written address
+        let first = address.saturating_sub(MAX_INSTRUCTION_SIZE - 1);
+        for decoded in &mut self.decoded[first..=address] {
+            if decoded.is_some() {
+                *decoded = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    //moves past an instruction of `length` values
+    #[inline]
+    fn advance(&mut self, length: u32) -> Result<(), Fault> {
+        match self.instruction_pointer.checked_add(length) {
+            Some(pointer) => {
+                self.instruction_pointer = pointer;
+                Ok(())
+            }
+            None => Err(Fault::InstructionPointerOutOfRange(
+                i64::from(self.instruction_pointer) + i64::from(length),
+            )),
+        }
+    }
+
+    fn jump(&mut self, target: i64) -> Result<(), Fault> {
+        match u32::try_from(target) {
+            Ok(pointer) => {
+                self.instruction_pointer = pointer;
+                Ok(())
+            }
+            Err(_) => Err(Fault::InstructionPointerOutOfRange(target)),
+        }
+    }
+
+    /// Executes a single instruction.
+    pub fn step(&mut self) -> Result<ComputeResult, IntcodeError> {
+        self.execute().map_err(|fault| self.error(fault))
+    }
+
+    //faults are small, so they're cheap to pass around until they become an error
+    #[inline]
+    fn execute(&mut self) -> Result<ComputeResult, Fault> {
+        let offset = self.instruction_pointer as usize;
+
+        let op = match self.decoded.get(offset) {
+            Some(Some(op)) => *op,
+            Some(None) => {
+                let op = decode(&self.memory, offset);
+                self.decoded[offset] = Some(op);
+                op
+            }
+            None => return Err(Fault::InstructionPointerOutOfRange(offset as i64)),
+        };
+
+        match op {
+            Op::Add(x, y, t) => {
+                let value = self.value(x)?.wrapping_add(self.value(y)?);
+                let address = self.target(t)?;
+                self.write(address, value)?;
+                self.advance(4)?;
+            }
+            Op::Multiply(x, y, t) => {
+                let value = self.value(x)?.wrapping_mul(self.value(y)?);
+                let address = self.target(t)?;
+                self.write(address, value)?;
+                self.advance(4)?;
+            }
+            Op::Input(t) => {
+                let address = self.target(t)?;
+
+                match self.input.pop_front() {
+                    Some(v) => {
+                        self.write(address, v)?;
+                        self.advance(2)?;
+                    }
+                    None => return Ok(WaitingForInput),
+                }
+            }
+            Op::Output(x) => {
+                let value = self.value(x)?;
+                self.output.push(value);
+                self.advance(2)?;
+            }
+            Op::JumpIfTrue(x, y) => {
+                let (condition, target) = (self.value(x)?, self.value(y)?);
+
+                if condition != 0 {
+                    self.jump(target)?;
+                } else {
+                    self.advance(3)?;
+                }
+            }
+            Op::JumpIfFalse(x, y) => {
+                let (condition, target) = (self.value(x)?, self.value(y)?);
+
+                if condition == 0 {
+                    self.jump(target)?;
+                } else {
+                    self.advance(3)?;
+                }
+            }
+            Op::LessThan(x, y, t) => {
+                let value = (self.value(x)? < self.value(y)?) as i64;
+                let address = self.target(t)?;
+                self.write(address, value)?;
+                self.advance(4)?;
+            }
+            Op::Equals(x, y, t) => {
+                let value = (self.value(x)? == self.value(y)?) as i64;
+                let address = self.target(t)?;
+                self.write(address, value)?;
+                self.advance(4)?;
+            }
+            Op::AdjustRelativeBase(x) => {
+                self.relative_base = self.relative_base.wrapping_add(self.value(x)?);
+                self.advance(2)?;
+            }
+            Op::Halt => return Ok(Halt),
+            Op::Invalid(invalid) => return Err(Fault::Invalid(invalid)),
+        }
+
+        Ok(CanContinue)
+    }
+
+    /// Runs until the next value is written to the output, in which case
+    /// `CanContinue` is returned, or until the machine halts or needs input.
+    pub fn run_until_output(&mut self) -> Result<ComputeResult, IntcodeError> {
+        let produced = self.output.len();
+
+        loop {
+            match self.step()? {
+                CanContinue if self.output.len() == produced => continue,
+                r => break Ok(r),
+            }
+        }
+    }
+
+    /// Runs until the machine halts or waits for input that isn't there.
+    pub fn run_until_input(&mut self) -> Result<ComputeResult, IntcodeError> {
+        loop {
+            match self.step()? {
+                CanContinue => continue,
+                r => break Ok(r),
+            }
+        }
+    }
+
+    /// Runs until the machine halts, treating running out of input as an error.
+    pub fn run_to_halt(&mut self) -> Result<(), IntcodeError> {
+        match self.run_until_input()? {
+            WaitingForInput => Err(IntcodeError::InputExhausted {
+                instruction_pointer: self.instruction_pointer,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Like `intcode::computer`, but on the fast interpreter.
+pub fn computer(intcode: &str, input: Vec<i64>) -> Result<Vec<i64>, IntcodeError> {
+    let mut machine = FastMachine::parse(intcode)?;
+    machine.extend_input(input);
+
+    machine.run_until_input().map(|_| machine.take_output())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fast::{computer, FastMachine};
+    use crate::intcode;
+    use crate::intcode::{ComputeResult, IntcodeError};
+    use crate::puzzle_input;
+
+    fn assert_same(program: &str, input: Vec<i64>) {
+        assert_eq!(
+            computer(program, input.clone()),
+            intcode::computer(program, input)
+        );
+    }
+
+    #[test]
+    fn same_results_as_the_interpreter() {
+        assert_same("3,0,4,0,99", vec![55]);
+        assert_same("1002,4,3,4,33", vec![]);
+        assert_same(
+            "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
+            vec![],
+        );
+        assert_same("104,1125899906842624,99", vec![]);
+        assert_same("1102,34915192,34915192,7,4,7,99,0", vec![]);
+        assert_same("1,12,10,1109,4,1109,99,0,0,0,0,0,7", vec![]);
+        assert_same("1,1109,12,10,4,10,99,0,0,0,0,0,7", vec![]);
+        assert_same(&puzzle_input!(5), vec![1]);
+        assert_same(&puzzle_input!(5), vec![5]);
+        assert_same(&puzzle_input!(9), vec![1]);
+        assert_same(&puzzle_input!(9), vec![2]);
+        assert_same(&puzzle_input!(7), vec![5, 0]);
+
+        //the robot only reads its input once it's woken up
+        let day17 = puzzle_input!(17);
+        assert_same(
+            &format!("2{}", &day17[1..]),
+            "n\n".bytes().map(|b| b as i64).collect(),
+        );
+
+        let day19 = puzzle_input!(19);
+
+        for x in 0..10 {
+            for y in 0..10 {
+                assert_same(&day19, vec![x, y]);
+            }
+        }
+    }
+
+    #[test]
+    fn same_errors_as_the_interpreter() {
+        assert_same("42,0,0,0,99", vec![]);
+        assert_same("1101,1,2,5,307,0,99", vec![]);
+        assert_same("1,-1,0,0,99", vec![]);
+        assert_same("11101,1,2,0,99", vec![]);
+        assert_same("1105,1,-7", vec![]);
+        assert_same("1101,1,2,0", vec![]);
+        assert_same("", vec![]);
+        assert_same("1,x", vec![]);
+        //beyond the memory limit
+        assert_same("1101,0,0,1000000000000000,99", vec![]);
+        assert_same("109,1000000000000000,21101,1,2,0,99", vec![]);
+        assert_same("109,1000000000000000,204,0,99", vec![]);
+        assert_same("109,9223372036854775807,109,1,204,0,99", vec![]);
+        assert_same("3,1000000000000000,99", vec![1]);
+    }
+
+    #[test]
+    fn the_limit_can_be_changed() {
+        let mut machine = FastMachine::parse("1101,1,2,100,99").unwrap();
+        machine.set_limit(Some(64));
+        assert_eq!(machine.limit(), Some(64));
+
+        assert_eq!(
+            machine.run_to_halt(),
+            Err(IntcodeError::MemoryLimitExceeded {
+                instruction_pointer: 0,
+                opcode: 1101,
+                address: 100
+            })
+        );
+        assert_eq!(machine.instruction_pointer(), 0);
+
+        machine.set_limit(None);
+        assert_eq!(machine.run_to_halt(), Ok(()));
+        assert_eq!(machine.memory()[100], 3);
+    }
+
+    #[test]
+    fn self_modifying_code_invalidates_the_cache() {
+        //the loop body outputs the value at address 9, then rewrites
+        //that parameter into an immediate, so the second pass outputs 9
+        let mut machine = FastMachine::parse("4,9,1101,104,0,0,1105,1,0,4").unwrap();
+
+        assert_eq!(machine.run_until_output(), Ok(ComputeResult::CanContinue));
+        assert_eq!(machine.run_until_output(), Ok(ComputeResult::CanContinue));
+        assert_eq!(machine.take_output(), vec![4, 9]);
+    }
+}
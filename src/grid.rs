@@ -0,0 +1,347 @@
+use crate::ascii::AsciiText;
+use cgmath::Vector2;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+/// The integer types a position can be made of.
+pub trait Coordinate: Copy + Ord + Hash + fmt::Debug {
+    fn from_i64(value: i64) -> Option<Self>;
+
+    fn to_i64(self) -> i64;
+}
+
+macro_rules! coordinate {
+    ($($t:ty),*) => {
+        $(
+            impl Coordinate for $t {
+                fn from_i64(value: i64) -> Option<$t> {
+                    <$t>::try_from(value).ok()
+                }
+
+                fn to_i64(self) -> i64 {
+                    i64::from(self)
+                }
+            }
+        )*
+    };
+}
+
+coordinate!(i8, i16, i32, i64);
+
+/// A cell of a grid, x grows to the right and y grows down, like the rows of a screen.
+pub type Position<N = i32> = Vector2<N>;
+
+/// Moving a position would take it out of the range of its coordinate type.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CoordinateOverflow {
+    pub x: i64,
+    pub y: i64,
+    pub dx: i64,
+    pub dy: i64,
+}
+
+impl fmt::Display for CoordinateOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "moving ({}, {}) by ({}, {}) overflows the coordinates",
+            self.x, self.y, self.dx, self.dy
+        )
+    }
+}
+
+impl Error for CoordinateOverflow {}
+
+/// The position moved by the offset, or an error instead of wrapping around.
+pub fn step<N: Coordinate>(
+    position: Position<N>,
+    (dx, dy): (i64, i64),
+) -> Result<Position<N>, CoordinateOverflow> {
+    let (x, y) = (position.x.to_i64(), position.y.to_i64());
+
+    match (
+        x.checked_add(dx).and_then(N::from_i64),
+        y.checked_add(dy).and_then(N::from_i64),
+    ) {
+        (Some(x), Some(y)) => Ok(Position::new(x, y)),
+        _ => Err(CoordinateOverflow { x, y, dx, dy }),
+    }
+}
+
+fn origin<N: Coordinate>() -> Position<N> {
+    //every coordinate type has a zero
+    let zero = N::from_i64(0).unwrap();
+    Position::new(zero, zero)
+}
+
+/// The offsets of the four neighbours that share a side, clockwise from above.
+pub const ORTHOGONAL: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The offsets of all eight neighbours, clockwise from above.
+pub const ALL_AROUND: [(i64, i64); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// A sparse grid that only stores the cells that were set, and keeps track
+/// of the rectangle around them as they are inserted.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Grid<T, N: Coordinate = i32> {
+    cells: HashMap<Position<N>, T>,
+    bounds: Option<(Position<N>, Position<N>)>,
+}
+
+impl<T, N: Coordinate> Default for Grid<T, N> {
+    fn default() -> Grid<T, N> {
+        Grid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T, N: Coordinate> Grid<T, N> {
+    pub fn new() -> Grid<T, N> {
+        Grid::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, position: Position<N>) -> Option<&T> {
+        self.cells.get(&position)
+    }
+
+    pub fn get_mut(&mut self, position: Position<N>) -> Option<&mut T> {
+        self.cells.get_mut(&position)
+    }
+
+    /// Sets the cell, returning what was there before.
+    pub fn insert(&mut self, position: Position<N>, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                Position::new(min.x.min(position.x), min.y.min(position.y)),
+                Position::new(max.x.max(position.x), max.y.max(position.y)),
+            ),
+            None => (position, position),
+        });
+
+        self.cells.insert(position, value)
+    }
+
+    /// The top left and bottom right corners of the cells that were set.
+    pub fn bounds(&self) -> Option<(Position<N>, Position<N>)> {
+        self.bounds
+    }
+
+    /// The cells that were set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position<N>, &T)> {
+        self.cells.iter().map(|(p, v)| (*p, v))
+    }
+
+    fn around<'a>(
+        &'a self,
+        position: Position<N>,
+        offsets: &'a [(i64, i64)],
+    ) -> impl Iterator<Item = (Position<N>, &'a T)> + 'a {
+        //a neighbour beyond the range of the coordinates can't have been set
+        offsets.iter().filter_map(move |offset| {
+            let neighbour = step(position, *offset).ok()?;
+            self.get(neighbour).map(|v| (neighbour, v))
+        })
+    }
+
+    /// The neighbours above, to the right, below and to the left that were set.
+    pub fn neighbours(&self, position: Position<N>) -> impl Iterator<Item = (Position<N>, &T)> {
+        self.around(position, &ORTHOGONAL)
+    }
+
+    /// Like `neighbours`, including the diagonal ones.
+    pub fn neighbours_diagonal(
+        &self,
+        position: Position<N>,
+    ) -> impl Iterator<Item = (Position<N>, &T)> {
+        self.around(position, &ALL_AROUND)
+    }
+
+    /// Every row within the bounds from top to bottom, with `None` for the cells that weren't set.
+    pub fn rows(&self) -> Vec<Vec<Option<&T>>> {
+        //the bounds are set cells, so every coordinate in between fits
+        let cell = |x, y| Position::new(N::from_i64(x).unwrap(), N::from_i64(y).unwrap());
+
+        match self.bounds {
+            Some((min, max)) => (min.y.to_i64()..=max.y.to_i64())
+                .map(|y| {
+                    (min.x.to_i64()..=max.x.to_i64())
+                        .map(|x| self.get(cell(x, y)))
+                        .collect()
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// A grid with the rows of cells, the first cell of the first row at (0, 0).
+    /// Fails if there are more rows or columns than the coordinates can count.
+    pub fn from_dense(rows: Vec<Vec<T>>) -> Result<Grid<T, N>, CoordinateOverflow> {
+        let mut grid = Grid::new();
+
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, value) in row.into_iter().enumerate() {
+                grid.insert(step(origin(), (x as i64, y as i64))?, value);
+            }
+        }
+
+        Ok(grid)
+    }
+
+    /// Draws the grid with a character per cell, a line per row.
+    pub fn render<F: Fn(Option<&T>) -> char>(&self, draw: F) -> String {
+        self.rows()
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(&draw)
+                    .chain(Some('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+}
+
+impl<T: Clone, N: Coordinate> Grid<T, N> {
+    /// The rows within the bounds, with `missing` for the cells that weren't set.
+    pub fn to_dense(&self, missing: T) -> Vec<Vec<T>> {
+        self.rows()
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|v| v.cloned().unwrap_or_else(|| missing.clone()))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl<N: Coordinate> Grid<char, N> {
+    /// A grid with a cell for every character of the text, a row per line.
+    pub fn parse(text: &str) -> Result<Grid<char, N>, CoordinateOverflow> {
+        Grid::from_dense(text.lines().map(|line| line.chars().collect()).collect())
+    }
+
+    /// Like `parse` for the ASCII output of a machine, values that aren't ASCII are left out.
+    pub fn from_ascii(values: &[i64]) -> Result<Grid<char, N>, CoordinateOverflow> {
+        Grid::parse(&AsciiText::decode(values).text)
+    }
+}
+
+/// Every cell with its `Display`, a space for the cells that weren't set.
+impl<T: fmt::Display, N: Coordinate> fmt::Display for Grid<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                match cell {
+                    Some(v) => write!(f, "{}", v)?,
+                    None => write!(f, " ")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{step, CoordinateOverflow, Grid, Position};
+
+    #[test]
+    fn keeps_track_of_the_bounds() {
+        let mut grid = Grid::new();
+        assert_eq!(grid.bounds(), None);
+
+        grid.insert(Position::new(2, 1), 'a');
+        grid.insert(Position::new(-1, 3), 'b');
+        assert_eq!(grid.insert(Position::new(2, 1), 'c'), Some('a'));
+
+        assert_eq!(
+            grid.bounds(),
+            Some((Position::new(-1, 1), Position::new(2, 3)))
+        );
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.to_string(), "   c\n    \nb   \n");
+        assert_eq!(
+            grid.render(|c| c.map_or('.', |c| c.to_ascii_uppercase())),
+            "...C\n....\nB...\n"
+        );
+    }
+
+    #[test]
+    fn parses_and_converts_to_rows() {
+        let output: Vec<i64> = "#.#\n.##\n"
+            .bytes()
+            .map(i64::from)
+            .chain(Some(1000))
+            .collect();
+        let grid = Grid::from_ascii(&output).unwrap();
+
+        assert_eq!(grid, Grid::<char>::parse("#.#\n.##").unwrap());
+        assert_eq!(
+            Grid::from_dense(grid.to_dense(' ')).unwrap(),
+            grid,
+            "converting to rows and back changes nothing"
+        );
+
+        let center = Position::new(1, 1);
+        let scaffold: Vec<Position> = grid
+            .neighbours(center)
+            .filter(|(_, c)| **c == '#')
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(scaffold, vec![Position::new(2, 1)]);
+        assert_eq!(grid.neighbours(center).count(), 3);
+        assert_eq!(grid.neighbours_diagonal(center).count(), 5);
+        assert_eq!(grid.neighbours(Position::new(5, 5)).count(), 0);
+    }
+
+    #[test]
+    fn movement_reports_overflow() {
+        let edge = Position::<i8>::new(127, -128);
+
+        assert_eq!(step(edge, (-1, 1)), Ok(Position::new(126, -127)));
+        assert_eq!(
+            step(edge, (1, 0)),
+            Err(CoordinateOverflow {
+                x: 127,
+                y: -128,
+                dx: 1,
+                dy: 0
+            })
+        );
+        assert!(step(Position::new(0, i64::MIN), (0, -1)).is_err());
+
+        let mut grid = Grid::<char, i8>::new();
+        grid.insert(edge, '#');
+        assert_eq!(grid.neighbours_diagonal(edge).count(), 0);
+
+        //a line of 200 characters doesn't fit in i8 coordinates, but does in i16
+        let wide = "#".repeat(200);
+        assert!(Grid::<char, i8>::parse(&wide).is_err());
+        assert_eq!(Grid::<char, i16>::parse(&wide).unwrap().len(), 200);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::direction::{Direction, Turn};
+use crate::grid::{CoordinateOverflow, Grid, Position};
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::{DenseMemory, Memory};
+use crate::ocr::{self, OcrError};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+/// The colour of a panel of the hull, as the camera and the brush of the painting robot code it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Panel {
+    Black = 0,
+    White = 1,
+}
+
+impl Panel {
+    pub fn from_code(code: i64) -> Option<Panel> {
+        match code {
+            0 => Some(Panel::Black),
+            1 => Some(Panel::White),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i64 {
+        self as i64
+    }
+}
+
+impl fmt::Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Panel::Black => write!(f, "."),
+            Panel::White => write!(f, "#"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RobotError {
+    Machine(IntcodeError),
+    UnknownColour(i64),
+    UnknownTurn(i64),
+    OffTheHull(CoordinateOverflow),
+    /// The program halted after painting a panel, without telling where to turn.
+    MissingTurn,
+}
+
+impl fmt::Display for RobotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RobotError::Machine(e) => write!(f, "the robot's brain failed: {}", e),
+            RobotError::UnknownColour(c) => write!(f, "can't paint with colour {}", c),
+            RobotError::UnknownTurn(t) => write!(f, "can't turn with {}", t),
+            RobotError::OffTheHull(e) => write!(f, "the robot left the hull: {}", e),
+            RobotError::MissingTurn => write!(f, "the robot halted before turning"),
+        }
+    }
+}
+
+impl Error for RobotError {}
+
+impl From<IntcodeError> for RobotError {
+    fn from(e: IntcodeError) -> RobotError {
+        RobotError::Machine(e)
+    }
+}
+
+impl From<CoordinateOverflow> for RobotError {
+    fn from(e: CoordinateOverflow) -> RobotError {
+        RobotError::OffTheHull(e)
+    }
+}
+
+//`#` for the white panels and `.` for the rest, a line per row
+fn render(panels: &Grid<Panel>) -> String {
+    panels.render(|panel| match panel {
+        Some(Panel::White) => '#',
+        _ => '.',
+    })
+}
+
+/// One instruction of the robot: it painted the panel it was on, turned and moved one panel forward.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Move {
+    pub from: Position,
+    pub colour: Panel,
+    pub turn: Turn,
+    pub to: Position,
+}
+
+/// The hull after the robot is done.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Hull {
+    /// Every panel the robot saw, the ones it didn't paint are still black.
+    pub panels: Grid<Panel>,
+    /// How many panels were painted at least once.
+    pub painted: usize,
+}
+
+impl Hull {
+    /// The hull with `#` for the white panels and `.` for the rest, a line per row.
+    pub fn render(&self) -> String {
+        render(&self.panels)
+    }
+
+    /// The letters painted in white.
+    pub fn letters(&self) -> Result<String, OcrError> {
+        ocr::read_grid(&self.panels, |panel| *panel == Panel::White)
+    }
+}
+
+/// A hull painting robot controlled by an Intcode program. It reads the colour of
+/// the panel under the robot, outputs the colour to paint it with and then the
+/// turn to make, 0 for left and 1 for right, after which the robot moves forward.
+/// The robot starts facing up, every panel it didn't see yet is black.
+pub struct HullRobot<M: Memory = DenseMemory> {
+    brain: Machine<M>,
+    position: Position,
+    direction: Direction,
+    panels: Grid<Panel>,
+    painted: HashSet<Position>,
+    halted: bool,
+}
+
+impl<M: Memory> HullRobot<M> {
+    /// A robot on a panel with the `start` colour.
+    pub fn new(brain: Machine<M>, start: Panel) -> HullRobot<M> {
+        let position = Position::new(0, 0);
+        let mut panels = Grid::new();
+        panels.insert(position, start);
+
+        HullRobot {
+            brain,
+            position,
+            direction: Direction::Up,
+            panels,
+            painted: HashSet::new(),
+            halted: false,
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn panels(&self) -> &Grid<Panel> {
+        &self.panels
+    }
+
+    /// The panels seen so far, rendered like `Hull::render`.
+    pub fn render(&self) -> String {
+        render(&self.panels)
+    }
+
+    /// How many panels were painted at least once so far.
+    pub fn painted(&self) -> usize {
+        self.painted.len()
+    }
+
+    /// The colour under the robot.
+    pub fn camera(&self) -> Panel {
+        self.panels
+            .get(self.position)
+            .copied()
+            .unwrap_or(Panel::Black)
+    }
+
+    //runs the brain until it outputs, showing it the camera whenever it asks, `None` once it halted
+    fn next_output(&mut self) -> Result<Option<i64>, RobotError> {
+        loop {
+            match self.brain.run_until_output()? {
+                ComputeResult::CanContinue => return Ok(self.brain.take_output().pop()),
+                ComputeResult::WaitingForInput => self.brain.push_input(self.camera().code()),
+                ComputeResult::Halt => return Ok(None),
+            }
+        }
+    }
+
+    /// Carries out the next instruction of the program, `None` once it halted.
+    pub fn step(&mut self) -> Result<Option<Move>, RobotError> {
+        if self.halted {
+            return Ok(None);
+        }
+
+        let colour = match self.next_output()? {
+            Some(c) => Panel::from_code(c).ok_or(RobotError::UnknownColour(c))?,
+            None => {
+                self.halted = true;
+                return Ok(None);
+            }
+        };
+        let turn = match self.next_output()? {
+            Some(t) => Turn::from_code(t).ok_or(RobotError::UnknownTurn(t))?,
+            None => return Err(RobotError::MissingTurn),
+        };
+
+        let from = self.position;
+        self.panels.insert(from, colour);
+        self.painted.insert(from);
+
+        self.direction = self.direction.turn(turn);
+        self.position = self.direction.step(from)?;
+        //the new panel shows up on the hull, even before it's painted
+        let camera = self.camera();
+        self.panels.insert(self.position, camera);
+
+        Ok(Some(Move {
+            from,
+            colour,
+            turn,
+            to: self.position,
+        }))
+    }
+
+    /// Runs the program until it halts.
+    pub fn run(self) -> Result<Hull, RobotError> {
+        self.run_with(|_, _| {})
+    }
+
+    /// Like `run`, calling `f` with the robot after every move, to show it at work.
+    pub fn run_with<F: FnMut(&HullRobot<M>, Move)>(mut self, mut f: F) -> Result<Hull, RobotError> {
+        while let Some(m) = self.step()? {
+            f(&self, m);
+        }
+
+        Ok(Hull {
+            painted: self.painted.len(),
+            panels: self.panels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::Turn;
+    use crate::hull::{HullRobot, Panel, RobotError};
+    use crate::intcode::Machine;
+    use crate::{puzzle_answer, puzzle_input};
+
+    fn robot(program: &str, start: Panel) -> HullRobot {
+        HullRobot::new(Machine::parse(program).unwrap(), start)
+    }
+
+    #[test]
+    fn paints_the_registration_identifier() {
+        let brain = Machine::parse(&puzzle_input!(11)).unwrap();
+
+        let hull = HullRobot::new(brain.clone(), Panel::Black).run().unwrap();
+        assert_eq!(hull.painted, puzzle_answer!(11, 1));
+
+        let mut moves = 0;
+        let mut frame = String::new();
+        let hull = HullRobot::new(brain, Panel::White)
+            .run_with(|robot, m| {
+                assert_eq!(robot.position(), m.to);
+                moves += 1;
+                frame = robot.render();
+            })
+            .unwrap();
+        assert!(moves >= hull.painted);
+        assert_eq!(frame, hull.render());
+        assert_eq!(
+            hull.render(),
+            "\
+.###...##..#..#.####.#..#..##....##.#..#...
+.#..#.#..#.#..#.#....#..#.#..#....#.#..#...
+.#..#.#....#..#.###..####.#.......#.####...
+.###..#.##.#..#.#....#..#.#.......#.#..#...
+.#....#..#.#..#.#....#..#.#..#.#..#.#..#...
+.#.....###..##..####.#..#..##...##..#..#...
+"
+        );
+        assert_eq!(hull.letters(), Ok(puzzle_answer!(11, 2)));
+    }
+
+    #[test]
+    fn reports_broken_programs() {
+        //paints the starting panel white, turns right, then reads the camera and halts
+        let mut r = robot("104,1,104,1,3,20,99", Panel::Black);
+        let m = r.step().unwrap().unwrap();
+        assert_eq!((m.colour, m.turn), (Panel::White, Turn::Right));
+        assert_eq!(r.camera(), Panel::Black);
+        assert_eq!(r.step(), Ok(None));
+        assert_eq!(r.painted(), 1);
+
+        let turn = robot("3,20,4,20,104,7,99", Panel::White).run();
+        assert_eq!(turn, Err(RobotError::UnknownTurn(7)));
+        let colour = robot("104,2,104,0,99", Panel::Black).run();
+        assert_eq!(colour, Err(RobotError::UnknownColour(2)));
+        assert_eq!(
+            robot("104,1,99", Panel::Black).run(),
+            Err(RobotError::MissingTurn)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::intcode::{IntcodeError, Machine};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Where the puzzle inputs are kept, as `dayNN.txt`, with the expected answers
+/// next to them in `dayNN.answers`, one line per part.
+pub const INPUTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/inputs");
+
+#[derive(Debug)]
+pub enum InputError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Parse(IntcodeError),
+    MissingAnswer {
+        day: u32,
+        part: usize,
+    },
+    InvalidAnswer {
+        day: u32,
+        part: usize,
+        answer: String,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io { path, error } => write!(f, "can't read {}: {}", path.display(), error),
+            InputError::Parse(e) => write!(f, "invalid Intcode: {}", e),
+            InputError::MissingAnswer { day, part } => {
+                write!(f, "there's no answer for day {} part {}", day, part)
+            }
+            InputError::InvalidAnswer { day, part, answer } => write!(
+                f,
+                "the answer '{}' for day {} part {} has the wrong type",
+                answer, day, part
+            ),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+pub fn input_path(day: u32) -> PathBuf {
+    Path::new(INPUTS_DIR).join(format!("day{:02}.txt", day))
+}
+
+pub fn answers_path(day: u32) -> PathBuf {
+    Path::new(INPUTS_DIR).join(format!("day{:02}.answers", day))
+}
+
+fn read(path: PathBuf) -> Result<String, InputError> {
+    fs::read_to_string(&path).map_err(|error| InputError::Io { path, error })
+}
+
+/// The input of the day without the surrounding whitespace and newlines.
+pub fn load(day: u32) -> Result<String, InputError> {
+    read(input_path(day)).map(|input| input.trim().to_string())
+}
+
+/// A machine loaded with the Intcode program of the day.
+pub fn machine(day: u32) -> Result<Machine, InputError> {
+    Machine::parse(&load(day)?).map_err(InputError::Parse)
+}
+
+/// The expected answer to part 1 or 2 of the day.
+pub fn answer<T: FromStr>(day: u32, part: usize) -> Result<T, InputError> {
+    let answers = read(answers_path(day))?;
+
+    let answer = answers
+        .lines()
+        .nth(part.wrapping_sub(1))
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .ok_or(InputError::MissingAnswer { day, part })?;
+
+    answer.parse().map_err(|_| InputError::InvalidAnswer {
+        day,
+        part,
+        answer: answer.to_string(),
+    })
+}
+
+/// The input of a day, panicking with a readable message when it can't be read.
+#[macro_export]
+macro_rules! puzzle_input {
+    ($day:expr) => {
+        $crate::inputs::load($day).unwrap_or_else(|e| panic!("{}", e))
+    };
+}
+
+/// The expected answer to a part of a day, panicking when there is none.
+#[macro_export]
+macro_rules! puzzle_answer {
+    ($day:expr, $part:expr) => {
+        $crate::inputs::answer($day, $part).unwrap_or_else(|e| panic!("{}", e))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inputs::{answer, load, machine, InputError};
+
+    #[test]
+    fn loads_inputs_and_answers() {
+        let input = load(5).unwrap();
+        assert!(input.starts_with("3,225,"));
+        assert!(input.ends_with("99,226"));
+
+        let mut machine = machine(5).unwrap();
+        machine.push_input(5);
+        machine.run_to_halt().unwrap();
+        assert_eq!(machine.take_output(), vec![answer(5, 2).unwrap()]);
+
+        assert!(matches!(
+            answer::<i64>(5, 3),
+            Err(InputError::MissingAnswer { day: 5, part: 3 })
+        ));
+        assert!(matches!(load(99), Err(InputError::Io { .. })));
+    }
+}
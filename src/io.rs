@@ -0,0 +1,389 @@
+use crate::ascii::{to_ascii, AsciiText, NotAscii};
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::Memory;
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, Sender};
+
+/// Provides the values read by the input instruction.
+pub trait InputSource {
+    /// The next input, or `None` if there is none yet, which makes the machine wait.
+    fn input(&mut self) -> Option<i64>;
+}
+
+/// Receives the values written by the output instruction.
+pub trait OutputSink {
+    fn output(&mut self, value: i64);
+}
+
+/// Something a machine talks to, like a robot, an arcade cabinet or a camera.
+pub trait IoDevice: InputSource + OutputSink {}
+
+impl<T: InputSource + OutputSink> IoDevice for T {}
+
+impl<T: InputSource + ?Sized> InputSource for &mut T {
+    fn input(&mut self) -> Option<i64> {
+        (**self).input()
+    }
+}
+
+impl<T: OutputSink + ?Sized> OutputSink for &mut T {
+    fn output(&mut self, value: i64) {
+        (**self).output(value)
+    }
+}
+
+impl InputSource for VecDeque<i64> {
+    fn input(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl OutputSink for VecDeque<i64> {
+    fn output(&mut self, value: i64) {
+        self.push_back(value)
+    }
+}
+
+impl OutputSink for Vec<i64> {
+    fn output(&mut self, value: i64) {
+        self.push(value)
+    }
+}
+
+/// Blocks until a value arrives, a closed channel means there won't be any input.
+impl InputSource for Receiver<i64> {
+    fn input(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+}
+
+impl OutputSink for Sender<i64> {
+    fn output(&mut self, value: i64) {
+        //nobody is listening anymore, so nobody misses the value
+        let _ = self.send(value);
+    }
+}
+
+/// A separate input source and output sink used as one device.
+pub struct Io<I, O> {
+    pub input: I,
+    pub output: O,
+}
+
+impl<I, O> Io<I, O> {
+    pub fn new(input: I, output: O) -> Io<I, O> {
+        Io { input, output }
+    }
+}
+
+impl<I: InputSource, O> InputSource for Io<I, O> {
+    fn input(&mut self) -> Option<i64> {
+        self.input.input()
+    }
+}
+
+impl<I, O: OutputSink> OutputSink for Io<I, O> {
+    fn output(&mut self, value: i64) {
+        self.output.output(value)
+    }
+}
+
+/// Input produced by a closure, see `input_fn`.
+pub struct InputFn<F>(F);
+
+/// Output handled by a closure, see `output_fn`.
+pub struct OutputFn<F>(F);
+
+pub fn input_fn<F: FnMut() -> Option<i64>>(f: F) -> InputFn<F> {
+    InputFn(f)
+}
+
+pub fn output_fn<F: FnMut(i64)>(f: F) -> OutputFn<F> {
+    OutputFn(f)
+}
+
+impl<F: FnMut() -> Option<i64>> InputSource for InputFn<F> {
+    fn input(&mut self) -> Option<i64> {
+        (self.0)()
+    }
+}
+
+impl<F: FnMut(i64)> OutputSink for OutputFn<F> {
+    fn output(&mut self, value: i64) {
+        (self.0)(value)
+    }
+}
+
+/// Feeds text to a machine as ASCII codes, see `ascii::to_ascii`.
+#[derive(Clone, Debug, Default)]
+pub struct AsciiInput {
+    pending: VecDeque<i64>,
+}
+
+impl AsciiInput {
+    pub fn new(text: &str) -> Result<AsciiInput, NotAscii> {
+        let mut input = AsciiInput::default();
+        input.push_str(text)?;
+        Ok(input)
+    }
+
+    /// Queues the text, nothing is queued when a character isn't ASCII.
+    pub fn push_str(&mut self, text: &str) -> Result<(), NotAscii> {
+        to_ascii(text).map(|values| self.pending.extend(values))
+    }
+
+    /// Queues `line` followed by a newline.
+    pub fn push_line(&mut self, line: &str) -> Result<(), NotAscii> {
+        self.push_str(line)?;
+        self.pending.push_back(i64::from(b'\n'));
+        Ok(())
+    }
+}
+
+impl InputSource for AsciiInput {
+    fn input(&mut self) -> Option<i64> {
+        self.pending.pop_front()
+    }
+}
+
+/// Collects output as text, keeping values that aren't ASCII apart as answers.
+impl OutputSink for AsciiText {
+    fn output(&mut self, value: i64) {
+        self.push(value)
+    }
+}
+
+/// The values a machine read and wrote, in order.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Tape {
+    pub inputs: Vec<i64>,
+    pub outputs: Vec<i64>,
+}
+
+impl Tape {
+    /// A device that provides the recorded inputs and checks the outputs against the recording.
+    pub fn replay(&self) -> Replay {
+        Replay {
+            inputs: self.inputs.iter().copied().collect(),
+            expected: self.outputs.clone(),
+            outputs: vec![],
+        }
+    }
+}
+
+/// Records everything that passes through a device on a tape.
+pub struct Recorder<D> {
+    device: D,
+    tape: Tape,
+}
+
+impl<D> Recorder<D> {
+    pub fn new(device: D) -> Recorder<D> {
+        Recorder {
+            device,
+            tape: Tape::default(),
+        }
+    }
+
+    pub fn tape(&self) -> &Tape {
+        &self.tape
+    }
+
+    pub fn into_parts(self) -> (D, Tape) {
+        (self.device, self.tape)
+    }
+}
+
+impl<D: InputSource> InputSource for Recorder<D> {
+    fn input(&mut self) -> Option<i64> {
+        let value = self.device.input()?;
+        self.tape.inputs.push(value);
+        Some(value)
+    }
+}
+
+impl<D: OutputSink> OutputSink for Recorder<D> {
+    fn output(&mut self, value: i64) {
+        self.tape.outputs.push(value);
+        self.device.output(value)
+    }
+}
+
+/// Plays a tape back, see `Tape::replay`.
+pub struct Replay {
+    inputs: VecDeque<i64>,
+    expected: Vec<i64>,
+    outputs: Vec<i64>,
+}
+
+impl Replay {
+    /// The index of the first output that differs from the recording, or of the
+    /// first missing or additional output.
+    pub fn divergence(&self) -> Option<usize> {
+        let differs = self
+            .outputs
+            .iter()
+            .zip(&self.expected)
+            .position(|(actual, expected)| actual != expected);
+
+        match differs {
+            Some(index) => Some(index),
+            None if self.outputs.len() != self.expected.len() => {
+                Some(self.outputs.len().min(self.expected.len()))
+            }
+            None => None,
+        }
+    }
+
+    pub fn outputs(&self) -> &[i64] {
+        &self.outputs
+    }
+}
+
+impl InputSource for Replay {
+    fn input(&mut self) -> Option<i64> {
+        self.inputs.pop_front()
+    }
+}
+
+impl OutputSink for Replay {
+    fn output(&mut self, value: i64) {
+        self.outputs.push(value)
+    }
+}
+
+impl<M: Memory> Machine<M> {
+    /// Runs the machine connected to a device, asking it for input when the
+    /// input buffer is empty and passing on every output as soon as it is written.
+    /// Returns `WaitingForInput` when the device has no input to give.
+    pub fn run_device(&mut self, device: &mut dyn IoDevice) -> Result<ComputeResult, IntcodeError> {
+        loop {
+            let result = self.step()?;
+
+            for value in self.take_output() {
+                device.output(value);
+            }
+
+            match result {
+                ComputeResult::CanContinue => continue,
+                ComputeResult::WaitingForInput => match device.input() {
+                    Some(value) => self.push_input(value),
+                    None => break Ok(ComputeResult::WaitingForInput),
+                },
+                ComputeResult::Halt => break Ok(ComputeResult::Halt),
+            }
+        }
+    }
+
+    /// Like `run_device`, with separate input and output.
+    pub fn run_io<I: InputSource, O: OutputSink>(
+        &mut self,
+        input: I,
+        output: O,
+    ) -> Result<ComputeResult, IntcodeError> {
+        self.run_device(&mut Io::new(input, output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ascii::AsciiText;
+    use crate::intcode::tests::doubler;
+    use crate::intcode::{ComputeResult, Machine};
+    use crate::io::{input_fn, output_fn, AsciiInput, InputSource, Io, OutputSink, Recorder};
+    use std::collections::VecDeque;
+
+    //turns left for a black panel, right for a white one and paints the opposite color
+    struct Robot {
+        camera: i64,
+        commands: Vec<i64>,
+    }
+
+    impl InputSource for Robot {
+        fn input(&mut self) -> Option<i64> {
+            Some(self.camera).filter(|_| self.commands.len() < 6)
+        }
+    }
+
+    impl OutputSink for Robot {
+        fn output(&mut self, value: i64) {
+            self.commands.push(value);
+            self.camera = 1 - self.camera;
+        }
+    }
+
+    #[test]
+    fn runs_with_queues_and_closures() {
+        let mut output = vec![];
+        let result = doubler().run_io(VecDeque::from(vec![1, 2, 3]), &mut output);
+
+        assert_eq!(result, Ok(ComputeResult::WaitingForInput));
+        assert_eq!(output, vec![2, 4, 6]);
+
+        let mut next = 0;
+        let mut sum = 0;
+        doubler()
+            .run_io(
+                input_fn(|| {
+                    next += 1;
+                    Some(next).filter(|&n| n <= 4)
+                }),
+                output_fn(|v| sum += v),
+            )
+            .unwrap();
+        assert_eq!(sum, 20);
+    }
+
+    #[test]
+    fn devices_answer_the_machine() {
+        let mut robot = Robot {
+            camera: 0,
+            commands: vec![],
+        };
+
+        doubler().run_device(&mut robot).unwrap();
+        assert_eq!(robot.commands, vec![0, 2, 0, 2, 0, 2]);
+    }
+
+    #[test]
+    fn talks_ascii() {
+        //upper cases the input by subtracting 32, until it reads a newline, then outputs 1000
+        let program =
+            "3,100,1008,100,10,101,1005,101,20,1001,100,-32,100,4,100,1105,1,0,99,99,104,1000,99";
+        let mut output = AsciiText::default();
+
+        let mut input = AsciiInput::new("").unwrap();
+        assert!(input.push_line("äbc").is_err());
+        input.push_line("abc").unwrap();
+
+        Machine::parse(program)
+            .unwrap()
+            .run_io(input, &mut output)
+            .unwrap();
+        assert_eq!(output.text, "ABC");
+        assert_eq!(output.answers, vec![1000]);
+    }
+
+    #[test]
+    fn replays_a_recording() {
+        let mut recorder = Recorder::new(Io::new(VecDeque::from(vec![5, 7]), vec![]));
+        doubler().run_device(&mut recorder).unwrap();
+
+        let (_, tape) = recorder.into_parts();
+        assert_eq!(tape.inputs, vec![5, 7]);
+        assert_eq!(tape.outputs, vec![10, 14]);
+
+        let mut replay = tape.replay();
+        doubler().run_device(&mut replay).unwrap();
+        assert_eq!(replay.divergence(), None);
+
+        //a program that triples instead of doubling
+        let mut replay = tape.replay();
+        Machine::parse("3,11,102,3,11,11,4,11,1105,1,0")
+            .unwrap()
+            .run_device(&mut replay)
+            .unwrap();
+        assert_eq!(replay.outputs(), &[15, 21]);
+        assert_eq!(replay.divergence(), Some(0));
+    }
+}
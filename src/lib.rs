@@ -1 +1,23 @@
+pub mod amplifier;
+pub mod ascii;
+pub mod assemble;
+pub mod asynchronous;
+pub mod days;
+pub mod debugger;
+pub mod direction;
+pub mod disassemble;
+pub mod fast;
+pub mod grid;
+pub mod hull;
+pub mod inputs;
 pub mod intcode;
+pub mod io;
+pub mod limits;
+pub mod memory;
+pub mod network;
+pub mod ocr;
+pub mod program;
+pub mod snapshot;
+pub mod solver;
+pub mod spawn;
+pub mod trace;
@@ -0,0 +1,208 @@
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::Memory;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Budgets for a single call to `Machine::run_with_limits`, `None` means unlimited.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Limits {
+    /// Instructions executed.
+    pub instructions: Option<u64>,
+    /// Memory size in cells, as the memory counts them, enforced by the memory before every write.
+    pub memory: Option<usize>,
+    /// Values written to the output.
+    pub output: Option<usize>,
+    /// Wall-clock time, checked every `DEADLINE_INTERVAL` instructions.
+    pub time: Option<Duration>,
+}
+
+pub const DEADLINE_INTERVAL: u64 = 1024;
+
+/// The budget that ran out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Limit {
+    Instructions,
+    Memory,
+    Output,
+    Time,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Instructions => write!(f, "instruction limit exceeded"),
+            Limit::Memory => write!(f, "memory limit exceeded"),
+            Limit::Output => write!(f, "output limit exceeded"),
+            Limit::Time => write!(f, "time limit exceeded"),
+        }
+    }
+}
+
+/// How a run with limits ended.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LimitedResult {
+    Halt,
+    WaitingForInput,
+    /// The machine stopped between two instructions and can be run again.
+    LimitExceeded(Limit),
+}
+
+impl<M: Memory> Machine<M> {
+    /// Like `run_until_input`, but stops as soon as one of the limits is exceeded,
+    /// leaving the machine in a state where it can be resumed with fresh limits.
+    /// A write the memory refuses because of its own limit, see `Memory::set_limit`,
+    /// is reported as the memory limit too.
+    pub fn run_with_limits(&mut self, limits: &Limits) -> Result<LimitedResult, IntcodeError> {
+        //the memory refuses the write that would exceed the limit, before it allocates anything
+        let own_limit = self.memory().limit();
+        let limit = match (own_limit, limits.memory) {
+            (Some(own), Some(run)) => Some(own.min(run)),
+            (own, run) => own.or(run),
+        };
+
+        self.memory_mut().set_limit(limit);
+        let result = self.run_limited(limits);
+        self.memory_mut().set_limit(own_limit);
+
+        match result {
+            Err(IntcodeError::MemoryLimitExceeded { .. }) => {
+                Ok(LimitedResult::LimitExceeded(Limit::Memory))
+            }
+            result => result,
+        }
+    }
+
+    fn run_limited(&mut self, limits: &Limits) -> Result<LimitedResult, IntcodeError> {
+        let start = Instant::now();
+        let produced = self.output().len();
+        let mut executed = 0;
+
+        loop {
+            if limits.instructions.is_some_and(|l| executed >= l) {
+                return Ok(LimitedResult::LimitExceeded(Limit::Instructions));
+            }
+
+            if executed % DEADLINE_INTERVAL == 0
+                && limits.time.is_some_and(|l| start.elapsed() >= l)
+            {
+                return Ok(LimitedResult::LimitExceeded(Limit::Time));
+            }
+
+            match self.step()? {
+                ComputeResult::Halt => return Ok(LimitedResult::Halt),
+                ComputeResult::WaitingForInput => return Ok(LimitedResult::WaitingForInput),
+                ComputeResult::CanContinue => executed += 1,
+            }
+
+            //the output may have been taken by the caller before this run
+            if limits
+                .output
+                .is_some_and(|l| self.output().len().saturating_sub(produced) >= l)
+            {
+                return Ok(LimitedResult::LimitExceeded(Limit::Output));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::Machine;
+    use crate::limits::{Limit, LimitedResult, Limits};
+    use crate::memory::{DenseMemory, Memory, DEFAULT_LIMIT};
+    use std::time::Duration;
+
+    //outputs 0, 1, 2, ... forever
+    fn counter() -> Machine {
+        Machine::parse("4,7,1001,7,1,7,1105,0").unwrap()
+    }
+
+    #[test]
+    fn resumes_after_the_instruction_limit() {
+        let mut machine = counter();
+        let limits = Limits {
+            instructions: Some(9),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            machine.run_with_limits(&limits),
+            Ok(LimitedResult::LimitExceeded(Limit::Instructions))
+        );
+        assert_eq!(machine.take_output(), vec![0, 1, 2]);
+
+        machine.run_with_limits(&limits).unwrap();
+        assert_eq!(machine.take_output(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn stops_at_the_other_limits() {
+        let output = Limits {
+            output: Some(5),
+            ..Limits::default()
+        };
+        let mut machine = counter();
+        assert_eq!(
+            machine.run_with_limits(&output),
+            Ok(LimitedResult::LimitExceeded(Limit::Output))
+        );
+        assert_eq!(machine.output(), &[0, 1, 2, 3, 4]);
+
+        let time = Limits {
+            time: Some(Duration::from_millis(10)),
+            ..Limits::default()
+        };
+        assert_eq!(
+            counter().run_with_limits(&time),
+            Ok(LimitedResult::LimitExceeded(Limit::Time))
+        );
+
+        let memory = Limits {
+            memory: Some(8),
+            ..Limits::default()
+        };
+        let mut machine = Machine::parse("1101,1,2,20,99").unwrap();
+        assert_eq!(
+            machine.run_with_limits(&memory),
+            Ok(LimitedResult::LimitExceeded(Limit::Memory))
+        );
+        assert_eq!(
+            machine.run_with_limits(&Limits::default()),
+            Ok(LimitedResult::Halt)
+        );
+    }
+
+    #[test]
+    fn refuses_to_allocate_beyond_the_memory_limit() {
+        let memory = Limits {
+            memory: Some(1000),
+            ..Limits::default()
+        };
+        let mut machine = Machine::parse("1101,1,2,100000000000,99").unwrap();
+
+        assert_eq!(
+            machine.run_with_limits(&memory),
+            Ok(LimitedResult::LimitExceeded(Limit::Memory))
+        );
+        assert_eq!(machine.instruction_pointer(), 0);
+        assert_eq!(machine.memory().size(), 5);
+        assert_eq!(
+            machine.memory().limit(),
+            Some(DEFAULT_LIMIT),
+            "the limit only holds for the run"
+        );
+
+        //the memory's own limit stops the machine the same way, and it resumes once raised
+        let mut machine = Machine::with_memory(DenseMemory::with_limit(vec![3, 20, 4, 20, 99], 8));
+        machine.push_input(7);
+        assert_eq!(
+            machine.run_with_limits(&Limits::default()),
+            Ok(LimitedResult::LimitExceeded(Limit::Memory))
+        );
+        assert_eq!(machine.input().len(), 1, "the input is still there");
+
+        machine.memory_mut().set_limit(Some(32));
+        assert_eq!(machine.run_with_limits(&memory), Ok(LimitedResult::Halt));
+        assert_eq!(machine.take_output(), vec![7]);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// A write that would make the memory allocate more cells than its limit allows.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MemoryLimitExceeded {
+    pub address: usize,
+    pub limit: usize,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "writing to address {} exceeds the memory limit of {} cells",
+            self.address, self.limit
+        )
+    }
+}
+
+impl Error for MemoryLimitExceeded {}
+
+/// Storage for the memory of a machine. Every address can be read, the ones
+/// that were never written read as zero.
+pub trait Memory: Clone {
+    fn read(&self, address: usize) -> i64;
+
+    fn write(&mut self, address: usize, value: i64) -> Result<(), MemoryLimitExceeded>;
+
+    /// One past the highest address that was loaded or written.
+    fn size(&self) -> usize;
+
+    /// The most cells the memory may allocate, `None` if it may grow as it likes.
+    fn limit(&self) -> Option<usize>;
+
+    /// Changes the limit, cells that were allocated already are kept.
+    /// Writes that would allocate beyond the limit fail and change nothing.
+    fn set_limit(&mut self, limit: Option<usize>);
+}
+
+/// The limit of `DenseMemory::new`, 512 MiB worth of cells. Programs that
+/// write further away need `PagedMemory`, or to lift the limit on purpose.
+pub const DEFAULT_LIMIT: usize = 1 << 26;
+
+/// A vector that grows up to the highest address written, fast for the usual
+/// programs, but writing far away allocates everything in between.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DenseMemory {
+    cells: Vec<i64>,
+    limit: Option<usize>,
+}
+
+impl DenseMemory {
+    /// Memory limited to `DEFAULT_LIMIT` cells.
+    pub fn new(intcode: Vec<i64>) -> DenseMemory {
+        DenseMemory::with_limit(intcode, DEFAULT_LIMIT)
+    }
+
+    /// Memory that refuses to grow beyond `limit` cells.
+    pub fn with_limit(intcode: Vec<i64>, limit: usize) -> DenseMemory {
+        DenseMemory::load(intcode, Some(limit))
+    }
+
+    pub(crate) fn load(intcode: Vec<i64>, limit: Option<usize>) -> DenseMemory {
+        DenseMemory {
+            cells: intcode,
+            limit,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[i64] {
+        &self.cells
+    }
+
+    /// The memory from address 0 up to `size`. Sparse memory has no such method,
+    /// its size may be far larger than the cells it allocated.
+    pub fn to_vec(&self) -> Vec<i64> {
+        self.cells.clone()
+    }
+}
+
+impl Memory for DenseMemory {
+    fn read(&self, address: usize) -> i64 {
+        self.cells.get(address).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<(), MemoryLimitExceeded> {
+        //extend the memory if necessary
+        if address >= self.cells.len() {
+            if let Some(limit) = self.limit {
+                if address >= limit {
+                    return Err(MemoryLimitExceeded { address, limit });
+                }
+            }
+
+            self.cells.resize(address + 1, 0);
+        }
+
+        self.cells[address] = value;
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit
+    }
+}
+
+pub const PAGE_SIZE: usize = 1024;
+
+/// Memory allocated in pages of `PAGE_SIZE` cells, only for the pages that
+/// were written, so programs can use addresses as large as they like.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PagedMemory {
+    pages: HashMap<usize, Box<[i64]>>,
+    size: usize,
+    limit: Option<usize>,
+}
+
+impl PagedMemory {
+    pub fn new(intcode: Vec<i64>) -> PagedMemory {
+        PagedMemory::load(intcode, None)
+    }
+
+    /// Memory that refuses to allocate more than `limit` cells, rounded up to whole pages.
+    pub fn with_limit(intcode: Vec<i64>, limit: usize) -> PagedMemory {
+        PagedMemory::load(intcode, Some(limit))
+    }
+
+    fn load(intcode: Vec<i64>, limit: Option<usize>) -> PagedMemory {
+        let mut pages = HashMap::new();
+
+        for (page, chunk) in intcode.chunks(PAGE_SIZE).enumerate() {
+            let mut cells = vec![0; PAGE_SIZE];
+            cells[..chunk.len()].copy_from_slice(chunk);
+            pages.insert(page, cells.into_boxed_slice());
+        }
+
+        PagedMemory {
+            pages,
+            size: intcode.len(),
+            limit,
+        }
+    }
+
+    /// The number of allocated pages.
+    pub fn pages(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+impl Memory for PagedMemory {
+    fn read(&self, address: usize) -> i64 {
+        self.pages
+            .get(&(address / PAGE_SIZE))
+            .map_or(0, |page| page[address % PAGE_SIZE])
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<(), MemoryLimitExceeded> {
+        let page = address / PAGE_SIZE;
+
+        if !self.pages.contains_key(&page) {
+            if let Some(limit) = self.limit {
+                if (self.pages.len() + 1) * PAGE_SIZE > limit.max(PAGE_SIZE) {
+                    return Err(MemoryLimitExceeded { address, limit });
+                }
+            }
+
+            self.pages
+                .insert(page, vec![0; PAGE_SIZE].into_boxed_slice());
+        }
+
+        self.pages.get_mut(&page).unwrap()[address % PAGE_SIZE] = value;
+        self.size = self.size.max(address + 1);
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::{DenseMemory, Memory, MemoryLimitExceeded, PagedMemory, PAGE_SIZE};
+
+    #[test]
+    fn dense_memory_grows_up_to_its_limit() {
+        let mut memory = DenseMemory::with_limit(vec![1, 2, 3], 10);
+
+        assert_eq!(memory.read(100), 0);
+        assert_eq!(memory.write(9, 7), Ok(()));
+        assert_eq!(memory.size(), 10);
+        assert_eq!(
+            memory.write(10, 7),
+            Err(MemoryLimitExceeded {
+                address: 10,
+                limit: 10
+            })
+        );
+        assert_eq!(memory.to_vec(), vec![1, 2, 3, 0, 0, 0, 0, 0, 0, 7]);
+    }
+
+    #[test]
+    fn paged_memory_only_allocates_what_is_written() {
+        let mut memory = PagedMemory::with_limit(vec![1, 2, 3], 2 * PAGE_SIZE);
+        let far = 1_000_000_000_000;
+
+        assert_eq!(memory.write(far, 42), Ok(()));
+        assert_eq!(memory.read(far), 42);
+        assert_eq!(memory.read(far + 1), 0);
+        assert_eq!(memory.read(2), 3);
+        assert_eq!(memory.pages(), 2);
+        assert_eq!(memory.size(), far + 1);
+
+        //the limit allows two pages
+        assert_eq!(memory.write(far + 1, 43), Ok(()));
+        assert!(memory.write(PAGE_SIZE, 1).is_err());
+    }
+}
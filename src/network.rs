@@ -0,0 +1,319 @@
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::{DenseMemory, Memory};
+use std::collections::VecDeque;
+
+/// A packet written by a machine as the three outputs destination, x and y.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Packet {
+    pub source: i64,
+    pub destination: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Something that happened on the network, passed to the observers.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Event {
+    /// A packet was sent, to a machine, to the NAT or to an address nobody has.
+    Packet(Packet),
+    /// A round passed in which every queue was empty and nothing was sent.
+    Idle,
+    /// The NAT sent its last packet to wake the network up.
+    Wake(Packet),
+    /// Every machine halted, nothing is going to happen anymore.
+    Halted,
+}
+
+/// Receives the packets sent to `address`, and when the network is idle
+/// sends the last of them to `wake`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Nat {
+    pub address: i64,
+    pub wake: i64,
+}
+
+impl Default for Nat {
+    fn default() -> Nat {
+        Nat {
+            address: 255,
+            wake: 0,
+        }
+    }
+}
+
+type Observer = Box<dyn FnMut(&Event)>;
+
+/// Machines running the same program that send each other packets. The
+/// machines take turns in address order, so every run gives the same result.
+pub struct Network<M: Memory = DenseMemory> {
+    machines: Vec<Machine<M>>,
+    halted: Vec<bool>,
+    queues: Vec<VecDeque<(i64, i64)>>,
+    //outputs of a packet that isn't complete yet
+    partial: Vec<Vec<i64>>,
+    nat: Option<Nat>,
+    last_nat_packet: Option<Packet>,
+    observers: Vec<Observer>,
+}
+
+impl<M: Memory> Network<M> {
+    /// Boots `size` copies of the machine, each reading its address as first input.
+    pub fn new(machine: Machine<M>, size: usize) -> Network<M> {
+        let machines = (0..size)
+            .map(|address| {
+                let mut m = machine.clone();
+                m.push_input(address as i64);
+                m
+            })
+            .collect();
+
+        Network {
+            machines,
+            halted: vec![false; size],
+            queues: vec![VecDeque::new(); size],
+            partial: vec![vec![]; size],
+            nat: None,
+            last_nat_packet: None,
+            observers: vec![],
+        }
+    }
+
+    pub fn with_nat(mut self, nat: Nat) -> Network<M> {
+        self.nat = Some(nat);
+        self
+    }
+
+    /// Calls `observer` for every event, in the order they happen.
+    pub fn observe<F: FnMut(&Event) + 'static>(&mut self, observer: F) {
+        self.observers.push(Box::new(observer))
+    }
+
+    pub fn machines(&self) -> &[Machine<M>] {
+        &self.machines
+    }
+
+    /// The last packet the NAT received.
+    pub fn nat_packet(&self) -> Option<Packet> {
+        self.last_nat_packet
+    }
+
+    fn notify(&mut self, event: Event) {
+        for observer in &mut self.observers {
+            observer(&event)
+        }
+    }
+
+    fn deliver(&mut self, packet: Packet) {
+        if self.nat.map(|n| n.address) == Some(packet.destination) {
+            self.last_nat_packet = Some(packet);
+        } else if let Some(queue) = self.queues.get_mut(packet.destination as usize) {
+            //negative addresses wrap around to numbers too large to exist
+            queue.push_back((packet.x, packet.y));
+        }
+    }
+
+    /// Gives every machine that didn't halt a turn: it receives its queued packets,
+    /// or -1 if there are none, and runs until it needs input again.
+    /// Returns the events of the round.
+    pub fn round(&mut self) -> Result<Vec<Event>, IntcodeError> {
+        let mut events = vec![];
+        let mut idle = true;
+
+        for address in 0..self.machines.len() {
+            let machine = &mut self.machines[address];
+
+            //packets for a machine that halted are lost
+            if self.halted[address] {
+                self.queues[address].clear();
+                continue;
+            }
+
+            if self.queues[address].is_empty() {
+                machine.push_input(-1);
+            } else {
+                idle = false;
+                for (x, y) in self.queues[address].drain(..) {
+                    machine.extend_input(vec![x, y]);
+                }
+            }
+
+            //the machine reads all of its input before it gives up its turn
+            if machine.run_until_input()? == ComputeResult::Halt {
+                self.halted[address] = true;
+            }
+
+            let partial = &mut self.partial[address];
+            partial.extend(machine.take_output());
+
+            let complete = partial.len() - partial.len() % 3;
+            let packets: Vec<Packet> = partial
+                .drain(..complete)
+                .collect::<Vec<i64>>()
+                .chunks(3)
+                .map(|p| Packet {
+                    source: address as i64,
+                    destination: p[0],
+                    x: p[1],
+                    y: p[2],
+                })
+                .collect();
+
+            for packet in packets {
+                idle = false;
+                self.deliver(packet);
+                events.push(Event::Packet(packet));
+            }
+        }
+
+        if idle && self.queues.iter().all(|q| q.is_empty()) {
+            events.push(Event::Idle);
+
+            if let (Some(nat), Some(packet)) = (self.nat, self.last_nat_packet) {
+                let wake = Packet {
+                    source: nat.address,
+                    destination: nat.wake,
+                    ..packet
+                };
+                if let Some(queue) = self.queues.get_mut(nat.wake as usize) {
+                    queue.push_back((wake.x, wake.y));
+                }
+                events.push(Event::Wake(wake));
+            }
+        }
+
+        if self.halted.iter().all(|h| *h) {
+            events.push(Event::Halted);
+        }
+
+        for event in &events {
+            self.notify(*event);
+        }
+
+        Ok(events)
+    }
+
+    /// Runs rounds until `stop` returns true for an event, which is returned,
+    /// or until every machine halted, then `Event::Halted` is returned.
+    /// A network without a NAT that goes idle stays idle, so stop at `Event::Idle` then.
+    pub fn run_until<F: FnMut(&Event) -> bool>(
+        &mut self,
+        mut stop: F,
+    ) -> Result<Event, IntcodeError> {
+        loop {
+            let events = self.round()?;
+            let halted = events.contains(&Event::Halted);
+
+            if let Some(event) = events.into_iter().find(|e| stop(e)) {
+                break Ok(event);
+            }
+            if halted {
+                break Ok(Event::Halted);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assemble::assemble;
+    use crate::intcode::Machine;
+    use crate::network::{Event, Nat, Network, Packet};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    //machine 0 sends (2, 7) to 1, every machine that receives (x, y) sends (x + 1, y + 1) to x
+    fn relay() -> Machine {
+        Machine::new(
+            assemble(
+                "
+                        in [address]
+                        jt [address], #wait
+                        out #1
+                        out #2
+                        out #7
+                wait:   in [x]
+                        eq [x], #-1, [empty]
+                        jt [empty], #wait
+                        in [y]
+                        add [y], #1, [y]
+                        out [x]
+                        add [x], #1, [x]
+                        out [x]
+                        out [y]
+                        jt #1, #wait
+                address: dw 0
+                x:       dw 0
+                y:       dw 0
+                empty:   dw 0",
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn routes_packets_to_the_nat() {
+        let mut network = Network::new(relay(), 4).with_nat(Nat {
+            address: 4,
+            wake: 0,
+        });
+
+        let traffic = Rc::new(RefCell::new(vec![]));
+        let log = Rc::clone(&traffic);
+        network.observe(move |event| {
+            if let Event::Packet(p) = event {
+                log.borrow_mut().push((p.source, p.destination, p.x, p.y))
+            }
+        });
+
+        let first = network
+            .run_until(|e| matches!(e, Event::Packet(p) if p.destination == 4))
+            .unwrap();
+
+        assert_eq!(
+            first,
+            Event::Packet(Packet {
+                source: 3,
+                destination: 4,
+                x: 5,
+                y: 10
+            })
+        );
+        assert_eq!(
+            *traffic.borrow(),
+            vec![(0, 1, 2, 7), (1, 2, 3, 8), (2, 3, 4, 9), (3, 4, 5, 10)]
+        );
+    }
+
+    #[test]
+    fn the_nat_wakes_an_idle_network() {
+        let mut network = Network::new(relay(), 4).with_nat(Nat {
+            address: 4,
+            wake: 0,
+        });
+
+        //the first y the NAT sends twice in a row
+        let mut previous = None;
+        let repeated = network
+            .run_until(|e| match e {
+                Event::Wake(p) if previous == Some(p.y) => true,
+                Event::Wake(p) => {
+                    previous = Some(p.y);
+                    false
+                }
+                _ => false,
+            })
+            .unwrap();
+
+        assert!(matches!(repeated, Event::Wake(p) if p.y == 10 && p.destination == 0));
+
+        //without a NAT the network simply goes idle
+        let mut network = Network::new(relay(), 4);
+        assert_eq!(network.run_until(|e| *e == Event::Idle), Ok(Event::Idle));
+        assert_eq!(network.nat_packet(), None);
+
+        //machines that halted don't keep the network running, even when the NAT wakes it
+        let mut network =
+            Network::new(Machine::parse("3,0,99").unwrap(), 2).with_nat(Nat::default());
+        assert_eq!(network.run_until(|_| false), Ok(Event::Halted));
+    }
+}
@@ -0,0 +1,346 @@
+use crate::grid::{Coordinate, Grid};
+use std::error::Error;
+use std::fmt;
+
+/// The letters of the 4 pixels wide and 6 pixels high font, a glyph may be narrower or wider.
+const SMALL: &[(char, &[&str])] = &[
+    ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// The letters of the 6 pixels wide and 10 pixels high font.
+const LARGE: &[(char, &[&str])] = &[
+    (
+        'A',
+        &[
+            "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'B',
+        &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+            "#....#", "#####.",
+        ],
+    ),
+    (
+        'C',
+        &[
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#....#", ".####.",
+        ],
+    ),
+    (
+        'E',
+        &[
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'F',
+        &[
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'G',
+        &[
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+            "#...##", ".###.#",
+        ],
+    ),
+    (
+        'H',
+        &[
+            "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'J',
+        &[
+            "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+            "#...#.", ".###..",
+        ],
+    ),
+    (
+        'K',
+        &[
+            "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+            "#...#.", "#....#",
+        ],
+    ),
+    (
+        'L',
+        &[
+            "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'N',
+        &[
+            "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+            "#...##", "#....#",
+        ],
+    ),
+    (
+        'P',
+        &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'R',
+        &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'X',
+        &[
+            "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'Z',
+        &[
+            "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+];
+
+/// A glyph that isn't a letter of the font.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct UnknownGlyph {
+    /// Counting the glyphs from the left, from 0.
+    pub index: usize,
+    /// The leftmost column of the glyph in the rendering.
+    pub column: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum OcrError {
+    /// There are no lit pixels.
+    Empty,
+    /// The lit rows are neither as high as the small font nor as the large one.
+    UnknownHeight(usize),
+    /// `text` has a `?` for every glyph that wasn't recognised.
+    Unrecognised {
+        text: String,
+        glyphs: Vec<UnknownGlyph>,
+    },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::Empty => write!(f, "there are no letters"),
+            OcrError::UnknownHeight(height) => write!(
+                f,
+                "letters {} pixels high aren't 6 or 10 pixels high like the fonts",
+                height
+            ),
+            OcrError::Unrecognised { text, glyphs } => {
+                let positions: Vec<String> = glyphs
+                    .iter()
+                    .map(|g| format!("glyph {} at column {}", g.index, g.column))
+                    .collect();
+                write!(f, "can't read {}, in '{}'", positions.join(", "), text)
+            }
+        }
+    }
+}
+
+impl Error for OcrError {}
+
+//the glyph as lit pixels, without the empty columns on either side
+fn pixels(glyph: &[&str]) -> Vec<Vec<bool>> {
+    let rows: Vec<Vec<bool>> = glyph
+        .iter()
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect();
+    let lit = |x: usize| rows.iter().any(|row| row[x]);
+
+    let width = rows[0].len();
+    match (0..width).position(lit) {
+        Some(left) => {
+            let right = (0..width).rposition(lit).unwrap();
+            rows.iter().map(|row| row[left..=right].to_vec()).collect()
+        }
+        None => rows,
+    }
+}
+
+/// Reads the letters in rows of lit pixels. The letters are told apart by the
+/// empty columns between them, the font by the height of the lit rows.
+pub fn recognise(rows: &[Vec<bool>]) -> Result<String, OcrError> {
+    let lit_rows: Vec<&Vec<bool>> = match (
+        rows.iter().position(|row| row.contains(&true)),
+        rows.iter().rposition(|row| row.contains(&true)),
+    ) {
+        (Some(top), Some(bottom)) => rows[top..=bottom].iter().collect(),
+        _ => return Err(OcrError::Empty),
+    };
+
+    let font = match lit_rows.len() {
+        6 => SMALL,
+        10 => LARGE,
+        height => return Err(OcrError::UnknownHeight(height)),
+    };
+    let font: Vec<(char, Vec<Vec<bool>>)> = font.iter().map(|(c, g)| (*c, pixels(g))).collect();
+
+    let width = lit_rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let pixel = |x: usize, y: usize| lit_rows[y].get(x).copied().unwrap_or(false);
+    let lit_column = |x: usize| (0..lit_rows.len()).any(|y| pixel(x, y));
+
+    let mut text = String::new();
+    let mut unknown = vec![];
+    let mut x = 0;
+
+    while x < width {
+        if !lit_column(x) {
+            x += 1;
+            continue;
+        }
+
+        let left = x;
+        while x < width && lit_column(x) {
+            x += 1;
+        }
+        let glyph: Vec<Vec<bool>> = (0..lit_rows.len())
+            .map(|y| (left..x).map(|x| pixel(x, y)).collect())
+            .collect();
+
+        match font.iter().find(|(_, pixels)| *pixels == glyph) {
+            Some((c, _)) => text.push(*c),
+            None => {
+                unknown.push(UnknownGlyph {
+                    index: text.chars().count(),
+                    column: left,
+                });
+                text.push('?');
+            }
+        }
+    }
+
+    if unknown.is_empty() {
+        Ok(text)
+    } else {
+        Err(OcrError::Unrecognised {
+            text,
+            glyphs: unknown,
+        })
+    }
+}
+
+/// Reads the letters drawn with `#` or `█`, every other character is dark.
+pub fn read(rendering: &str) -> Result<String, OcrError> {
+    let rows: Vec<Vec<bool>> = rendering
+        .lines()
+        .map(|line| line.chars().map(|c| c == '#' || c == '█').collect())
+        .collect();
+
+    recognise(&rows)
+}
+
+/// Reads the letters on the grid, the cells that weren't set are dark.
+/// Columns are counted from the left of the bounds of the grid.
+pub fn read_grid<T, N: Coordinate, F: Fn(&T) -> bool>(
+    grid: &Grid<T, N>,
+    lit: F,
+) -> Result<String, OcrError> {
+    let rows: Vec<Vec<bool>> = grid
+        .rows()
+        .into_iter()
+        .map(|row| row.into_iter().map(|cell| cell.is_some_and(&lit)).collect())
+        .collect();
+
+    recognise(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use crate::ocr::{read, read_grid, OcrError, UnknownGlyph, LARGE, SMALL};
+
+    //draws the letters of the font next to each other, with `gap` empty columns in between
+    fn draw(font: &[(char, &[&str])], text: &str, gap: usize) -> String {
+        let glyphs: Vec<&[&str]> = text
+            .chars()
+            .map(|c| font.iter().find(|(l, _)| *l == c).unwrap().1)
+            .collect();
+
+        (0..glyphs[0].len())
+            .map(|y| {
+                let row: Vec<&str> = glyphs.iter().map(|g| g[y]).collect();
+                row.join(&".".repeat(gap)) + "\n"
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_both_fonts() {
+        let small: String = SMALL.iter().map(|(c, _)| c).collect();
+        let large: String = LARGE.iter().map(|(c, _)| c).collect();
+
+        assert_eq!(read(&draw(SMALL, &small, 1)), Ok(small));
+        assert_eq!(read(&draw(LARGE, &large, 2)), Ok(large.clone()));
+
+        //with empty rows and columns around the letters, and the large font in blocks
+        let blocks = draw(LARGE, "HXZ", 2).replace('#', "█");
+        let padded = format!("\n\n{}\n", blocks.replace('\n', "....\n"));
+        assert_eq!(read(&padded), Ok("HXZ".to_string()));
+
+        let grid = Grid::<char>::parse(&draw(SMALL, "JULY", 1)).unwrap();
+        assert_eq!(read_grid(&grid, |c| *c == '#'), Ok("JULY".to_string()));
+    }
+
+    #[test]
+    fn lists_what_it_cant_read() {
+        assert_eq!(read("...\n. .\n"), Err(OcrError::Empty));
+        assert_eq!(read("#\n#\n#\n"), Err(OcrError::UnknownHeight(3)));
+
+        let mut rendering = draw(SMALL, "ABC", 1);
+        //a dot inside the top of the B, the rows are 15 characters long
+        rendering.replace_range(15 + 6..15 + 7, "#");
+        let error = read(&rendering).unwrap_err();
+        assert_eq!(
+            error,
+            OcrError::Unrecognised {
+                text: "A?C".to_string(),
+                glyphs: vec![UnknownGlyph {
+                    index: 1,
+                    column: 5
+                }]
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "can't read glyph 1 at column 5, in 'A?C'"
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use crate::intcode::IntcodeError;
+use std::error::Error;
+use std::fmt;
+
+/// An Intcode program in text form, with the `key: value` lines of its
+/// optional header.
+///
+/// The text is comma-separated Intcode that may be spread over several lines,
+/// with whitespace around the values, a trailing comma and `#` comments that
+/// run to the end of the line. The header is every line before the first line
+/// of code, each with a colon in it, for example:
+///
+/// ```text
+/// # prints its input
+/// name: echo
+/// 3,0,4,0,   # in, out
+/// 99
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Program {
+    pub header: Vec<(String, String)>,
+    pub intcode: Vec<i64>,
+}
+
+//comments become spaces, so the byte offsets into the text stay the same
+fn blank_comments(text: &str) -> String {
+    text.split_inclusive('\n')
+        .map(|line| match line.find('#') {
+            Some(comment) => {
+                let newline = if line.ends_with('\n') { "\n" } else { "" };
+                let blanked = line.len() - comment - newline.len();
+                format!("{}{}{}", &line[..comment], " ".repeat(blanked), newline)
+            }
+            None => line.to_string(),
+        })
+        .collect()
+}
+
+fn parse_intcode(code: &str, offset: usize) -> Result<Vec<i64>, IntcodeError> {
+    let mut intcode = vec![];
+    let mut position = offset;
+    let tokens: Vec<&str> = code.split(',').collect();
+
+    for (i, raw) in tokens.iter().enumerate() {
+        let token = raw.trim();
+        let token_position = position + (raw.len() - raw.trim_start().len());
+        position += raw.len() + 1;
+
+        //a trailing comma or a program without code
+        if token.is_empty() && i == tokens.len() - 1 {
+            break;
+        }
+
+        intcode.push(token.parse().map_err(|_| IntcodeError::ParseError {
+            position: token_position,
+            token: token.to_string(),
+        })?);
+    }
+
+    Ok(intcode)
+}
+
+impl Program {
+    pub fn parse(text: &str) -> Result<Program, IntcodeError> {
+        let text = blank_comments(text);
+        let mut header = vec![];
+        let mut code = text.len();
+        let mut offset = 0;
+
+        for line in text.split_inclusive('\n') {
+            match line.split_once(':') {
+                _ if line.trim().is_empty() => {}
+                Some((key, value)) => {
+                    header.push((key.trim().to_string(), value.trim().to_string()))
+                }
+                None => {
+                    code = offset;
+                    break;
+                }
+            }
+            offset += line.len();
+        }
+
+        Ok(Program {
+            header,
+            intcode: parse_intcode(&text[code..], code)?,
+        })
+    }
+
+    /// The value of the first header line with the key.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.header
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+const MAGIC: &[u8; 4] = b"ICBN";
+
+/// How the values of a binary program are stored.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Encoding {
+    /// Eight bytes per value.
+    LittleEndian,
+    /// Zigzag LEB128, one byte for values between -64 and 63.
+    Varint,
+}
+
+impl Encoding {
+    fn tag(self) -> u8 {
+        match self {
+            Encoding::LittleEndian => 0,
+            Encoding::Varint => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BinaryError {
+    NotBinary,
+    UnknownEncoding(u8),
+    /// The program ends in the middle of the value at the offset.
+    Truncated {
+        offset: usize,
+    },
+    /// The varint at the offset doesn't fit in an `i64`.
+    Overflow {
+        offset: usize,
+    },
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryError::NotBinary => write!(f, "not a binary Intcode program"),
+            BinaryError::UnknownEncoding(e) => write!(f, "unknown encoding {}", e),
+            BinaryError::Truncated { offset } => {
+                write!(f, "the value at offset {} is cut off", offset)
+            }
+            BinaryError::Overflow { offset } => {
+                write!(f, "the value at offset {} is too large", offset)
+            }
+        }
+    }
+}
+
+impl Error for BinaryError {}
+
+/// Whether the bytes start like a program written by `encode`.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Writes the magic `ICBN`, a byte for the encoding and the values.
+pub fn encode(intcode: &[i64], encoding: Encoding) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(encoding.tag());
+
+    for &value in intcode {
+        match encoding {
+            Encoding::LittleEndian => bytes.extend_from_slice(&value.to_le_bytes()),
+            Encoding::Varint => {
+                let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+                while zigzag >= 0x80 {
+                    bytes.push(zigzag as u8 | 0x80);
+                    zigzag >>= 7;
+                }
+                bytes.push(zigzag as u8);
+            }
+        }
+    }
+
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<i64>, BinaryError> {
+    if !is_binary(bytes) {
+        return Err(BinaryError::NotBinary);
+    }
+
+    let start = MAGIC.len() + 1;
+    let values = bytes.get(start..).unwrap_or(&[]);
+
+    match bytes.get(MAGIC.len()) {
+        Some(0) => values
+            .chunks(8)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut value = [0; 8];
+                if chunk.len() < value.len() {
+                    return Err(BinaryError::Truncated {
+                        offset: start + i * 8,
+                    });
+                }
+                value.copy_from_slice(chunk);
+                Ok(i64::from_le_bytes(value))
+            })
+            .collect(),
+        Some(1) => {
+            let mut intcode = vec![];
+            let mut offset = start;
+
+            while offset < bytes.len() {
+                let value_offset = offset;
+                let mut zigzag = 0u64;
+                let mut shift = 0;
+
+                loop {
+                    let byte = *bytes.get(offset).ok_or(BinaryError::Truncated {
+                        offset: value_offset,
+                    })?;
+                    offset += 1;
+
+                    //the tenth byte may only hold the last bit
+                    if shift == 63 && byte > 1 {
+                        return Err(BinaryError::Overflow {
+                            offset: value_offset,
+                        });
+                    }
+
+                    zigzag |= u64::from(byte & 0x7f) << shift;
+                    shift += 7;
+
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                }
+
+                intcode.push((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64));
+            }
+
+            Ok(intcode)
+        }
+        Some(&e) => Err(BinaryError::UnknownEncoding(e)),
+        None => Err(BinaryError::Truncated {
+            offset: MAGIC.len(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::IntcodeError;
+    use crate::program::{decode, encode, BinaryError, Encoding, Program};
+
+    #[test]
+    fn parses_text_with_whitespace_comments_and_a_header() {
+        let text = "# prints its input\nname: echo\ninputs: 1\n\n3, 0,\n  4,0, # out\n99,\n";
+        let program = Program::parse(text).unwrap();
+
+        assert_eq!(program.intcode, vec![3, 0, 4, 0, 99]);
+        assert_eq!(program.value("name"), Some("echo"));
+        assert_eq!(program.value("inputs"), Some("1"));
+
+        assert_eq!(Program::parse(" \n# nothing\n"), Ok(Program::default()));
+
+        //offsets count bytes in the original text, comments included
+        assert_eq!(
+            Program::parse("# é\n1,\n 2 ,x3,99"),
+            Err(IntcodeError::ParseError {
+                position: 12,
+                token: "x3".to_string()
+            })
+        );
+        assert_eq!(
+            Program::parse("1,,2"),
+            Err(IntcodeError::ParseError {
+                position: 2,
+                token: "".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn round_trips_binary_programs() {
+        let intcode = vec![0, 1, -1, 63, -64, 64, 1_000_000, i64::MAX, i64::MIN];
+
+        for encoding in [Encoding::LittleEndian, Encoding::Varint] {
+            assert_eq!(decode(&encode(&intcode, encoding)), Ok(intcode.clone()));
+        }
+
+        assert_eq!(encode(&[1, -1, 64], Encoding::Varint)[5..], [2, 1, 128, 1]);
+
+        let mut truncated = encode(&[1, 1000], Encoding::Varint);
+        truncated.pop();
+        assert_eq!(
+            decode(&truncated),
+            Err(BinaryError::Truncated { offset: 6 })
+        );
+
+        assert_eq!(decode(b"3,0,4,0,99"), Err(BinaryError::NotBinary));
+        assert_eq!(decode(b"ICBN\x07"), Err(BinaryError::UnknownEncoding(7)));
+        assert_eq!(
+            decode(b"ICBN\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\x7f"),
+            Err(BinaryError::Overflow { offset: 5 })
+        );
+    }
+}
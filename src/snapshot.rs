@@ -0,0 +1,254 @@
+use crate::memory::DEFAULT_LIMIT;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"ICSN";
+
+/// The format version written by `write_to`, version 1 didn't have the memory limit.
+pub const VERSION: u16 = 2;
+
+/// The complete state of a machine, taken with `Machine::snapshot` and turned
+/// back into a machine with `Machine::restore`.
+///
+/// On disk a snapshot is the magic `ICSN`, a little-endian `u16` version, the
+/// `u32` instruction pointer, the `i64` relative base and the memory limit, a `u8` 1
+/// followed by the `u64` limit or a 0 without one. Then come the memory, the pending
+/// input and the output, each as a `u64` length and that many `i64`s.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Snapshot {
+    pub memory: Vec<i64>,
+    /// The limit of the memory, see `Memory::limit`.
+    pub limit: Option<usize>,
+    pub instruction_pointer: u32,
+    pub relative_base: i64,
+    pub input: Vec<i64>,
+    pub output: Vec<i64>,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    NotASnapshot,
+    UnsupportedVersion(u16),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "can't read or write the snapshot: {}", e),
+            SnapshotError::NotASnapshot => write!(f, "not an Intcode snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "snapshot version {} isn't supported", v)
+            }
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> SnapshotError {
+        SnapshotError::Io(e)
+    }
+}
+
+fn write_values<W: Write>(writer: &mut W, values: &[i64]) -> io::Result<()> {
+    writer.write_all(&(values.len() as u64).to_le_bytes())?;
+
+    for v in values {
+        writer.write_all(&v.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_values<R: Read>(reader: &mut R) -> io::Result<Vec<i64>> {
+    let length = u64::from_le_bytes(read_array(reader)?);
+
+    //don't trust the length with an allocation, a corrupt file runs out of bytes instead
+    let mut values = vec![];
+    for _ in 0..length {
+        values.push(i64::from_le_bytes(read_array(reader)?));
+    }
+
+    Ok(values)
+}
+
+impl Snapshot {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.instruction_pointer.to_le_bytes())?;
+        writer.write_all(&self.relative_base.to_le_bytes())?;
+        match self.limit {
+            Some(limit) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&(limit as u64).to_le_bytes())?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+        write_values(writer, &self.memory)?;
+        write_values(writer, &self.input)?;
+        write_values(writer, &self.output)
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Snapshot, SnapshotError> {
+        if &read_array::<R, 4>(reader)? != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version != 1 && version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let instruction_pointer = u32::from_le_bytes(read_array(reader)?);
+        let relative_base = i64::from_le_bytes(read_array(reader)?);
+        //machines saved before the limit was recorded get the default one
+        let limit = match version {
+            1 => Some(DEFAULT_LIMIT),
+            _ => match read_array::<R, 1>(reader)? {
+                [0] => None,
+                //a limit too large to address can't be reached anyway
+                _ => {
+                    let limit = u64::from_le_bytes(read_array(reader)?);
+                    Some(usize::try_from(limit).unwrap_or(usize::MAX))
+                }
+            },
+        };
+
+        Ok(Snapshot {
+            memory: read_values(reader)?,
+            limit,
+            instruction_pointer,
+            relative_base,
+            input: read_values(reader)?,
+            output: read_values(reader)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        //writing to a vector can't fail
+        self.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+        Snapshot::read_from(&mut bytes)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Snapshot, SnapshotError> {
+        Snapshot::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::{ComputeResult, Machine};
+    use crate::memory::{Memory, DEFAULT_LIMIT};
+    use crate::snapshot::{Snapshot, SnapshotError};
+
+    //adds every input to a running total and outputs it
+    fn accumulator() -> Machine {
+        Machine::parse("3,100,1,100,101,101,4,101,1105,1,0").unwrap()
+    }
+
+    #[test]
+    fn clones_continue_independently() {
+        let mut machine = accumulator();
+        machine.push_input(5);
+        assert_eq!(
+            machine.run_until_input(),
+            Ok(ComputeResult::WaitingForInput)
+        );
+
+        let mut fork = machine.clone();
+
+        machine.push_input(1);
+        fork.push_input(10);
+        machine.run_until_input().unwrap();
+        fork.run_until_input().unwrap();
+
+        assert_eq!(machine.take_output(), vec![5, 6]);
+        assert_eq!(fork.take_output(), vec![5, 15]);
+    }
+
+    #[test]
+    fn restores_from_bytes() {
+        let mut machine = accumulator();
+        machine.extend_input(vec![3, 4, 7]);
+        machine.step().unwrap();
+        machine.step().unwrap();
+
+        let bytes = machine.snapshot().to_bytes();
+        let mut restored = Machine::restore(Snapshot::from_bytes(&bytes).unwrap());
+
+        assert_eq!(restored.snapshot(), machine.snapshot());
+
+        machine.run_until_input().unwrap();
+        restored.run_until_input().unwrap();
+        assert_eq!(restored.take_output(), machine.take_output());
+    }
+
+    #[test]
+    fn keeps_the_memory_limit() {
+        let mut machine = accumulator();
+        machine.memory_mut().set_limit(Some(200));
+
+        let bytes = machine.snapshot().to_bytes();
+        let restored = Machine::restore(Snapshot::from_bytes(&bytes).unwrap());
+        assert_eq!(restored.memory().limit(), Some(200));
+
+        machine.memory_mut().set_limit(None);
+        let bytes = machine.snapshot().to_bytes();
+        let restored = Machine::restore(Snapshot::from_bytes(&bytes).unwrap());
+        assert_eq!(restored.memory().limit(), None);
+
+        //version 1 didn't record the limit, so there's the default one
+        let mut old = bytes[..18].to_vec();
+        old[4] = 1;
+        old.extend_from_slice(&bytes[19..]);
+        let restored = Machine::restore(Snapshot::from_bytes(&old).unwrap());
+        assert_eq!(restored.memory().limit(), Some(DEFAULT_LIMIT));
+        assert_eq!(restored.memory().to_vec(), machine.memory().to_vec());
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let bytes = accumulator().snapshot().to_bytes();
+
+        match Snapshot::from_bytes(b"1,2,3,99") {
+            Err(SnapshotError::NotASnapshot) => (),
+            r => panic!("unexpected {:?}", r),
+        }
+
+        let mut newer = bytes.clone();
+        newer[4] = 3;
+        match Snapshot::from_bytes(&newer) {
+            Err(SnapshotError::UnsupportedVersion(3)) => (),
+            r => panic!("unexpected {:?}", r),
+        }
+
+        match Snapshot::from_bytes(&bytes[..bytes.len() - 1]) {
+            Err(SnapshotError::Io(_)) => (),
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::inputs::{self, InputError};
+use std::error::Error;
+use std::io;
+use std::time::{Duration, Instant};
+
+pub type SolverResult<T> = Result<T, Box<dyn Error>>;
+
+/// The solution of a day: how to read its input and how to answer both parts.
+pub trait Solver {
+    const DAY: u32;
+
+    type Input;
+
+    fn parse(&self, input: &str) -> SolverResult<Self::Input>;
+
+    fn part_one(&self, input: &Self::Input) -> SolverResult<String>;
+
+    fn part_two(&self, input: &Self::Input) -> SolverResult<String>;
+}
+
+/// How an answer compares to the expected one stored with the input.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Check {
+    Correct,
+    Wrong {
+        expected: String,
+    },
+    /// There's no expected answer yet.
+    Unknown,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Part {
+    pub part: usize,
+    /// The answer, or the error that prevented finding it.
+    pub answer: Result<String, String>,
+    pub check: Check,
+    pub time: Duration,
+}
+
+/// The answers to both parts of a day.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Solution {
+    pub day: u32,
+    pub parse_time: Duration,
+    pub parts: Vec<Part>,
+}
+
+/// A `Solver` without its input type, so solvers of different days fit in one list.
+pub trait Puzzle {
+    fn day(&self) -> u32;
+
+    /// Solves both parts, an input that can't be parsed fails both.
+    fn solve(&self, input: &str) -> Solution;
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+impl<S: Solver> Puzzle for S {
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn solve(&self, input: &str) -> Solution {
+        let (parsed, parse_time) = timed(|| self.parse(input));
+
+        let parts = (1..=2)
+            .map(|part| {
+                let (answer, time) = timed(|| match &parsed {
+                    Ok(input) if part == 1 => self.part_one(input),
+                    Ok(input) => self.part_two(input),
+                    Err(e) => Err(format!("can't parse the input: {}", e).into()),
+                });
+
+                Part {
+                    part,
+                    answer: answer.map_err(|e| e.to_string()),
+                    check: Check::Unknown,
+                    time,
+                }
+            })
+            .collect();
+
+        Solution {
+            day: S::DAY,
+            parse_time,
+            parts,
+        }
+    }
+}
+
+/// Solves the puzzle for its input in the inputs directory and checks the
+/// answers against the expected ones. A day without an answers file isn't
+/// checked, its answers are `Check::Unknown`.
+pub fn run(puzzle: &dyn Puzzle) -> Result<Solution, InputError> {
+    let day = puzzle.day();
+    let mut solution = puzzle.solve(&inputs::load(day)?);
+
+    check(&mut solution, |part| inputs::answer(day, part))?;
+
+    Ok(solution)
+}
+
+fn check<F>(solution: &mut Solution, expected: F) -> Result<(), InputError>
+where
+    F: Fn(usize) -> Result<String, InputError>,
+{
+    for part in &mut solution.parts {
+        part.check = match (expected(part.part), &part.answer) {
+            (Ok(expected), Ok(answer)) if *answer == expected => Check::Correct,
+            (Ok(expected), _) => Check::Wrong { expected },
+            (Err(InputError::MissingAnswer { .. }), _) => Check::Unknown,
+            (Err(InputError::Io { error, .. }), _) if error.kind() == io::ErrorKind::NotFound => {
+                Check::Unknown
+            }
+            (Err(e), _) => return Err(e),
+        };
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inputs;
+    use crate::solver::{check, Check, Puzzle, Solver, SolverResult};
+
+    struct Sum;
+
+    impl Solver for Sum {
+        const DAY: u32 = 1;
+
+        type Input = Vec<i64>;
+
+        fn parse(&self, input: &str) -> SolverResult<Vec<i64>> {
+            Ok(input
+                .split(',')
+                .map(|v| v.parse())
+                .collect::<Result<_, _>>()?)
+        }
+
+        fn part_one(&self, input: &Vec<i64>) -> SolverResult<String> {
+            Ok(input.iter().sum::<i64>().to_string())
+        }
+
+        fn part_two(&self, _: &Vec<i64>) -> SolverResult<String> {
+            Err("not solved yet".into())
+        }
+    }
+
+    #[test]
+    fn solves_both_parts() {
+        let solution = Sum.solve("1,2,3");
+        assert_eq!(solution.day, 1);
+        assert_eq!(solution.parts[0].answer, Ok("6".to_string()));
+        assert_eq!(solution.parts[0].check, Check::Unknown);
+        assert_eq!(solution.parts[1].answer, Err("not solved yet".to_string()));
+
+        let solution = Sum.solve("1,x");
+        assert!(solution.parts.iter().all(|p| p.answer.is_err()));
+    }
+
+    #[test]
+    fn days_without_answers_are_unchecked() {
+        //there's no answers file for day 1
+        let mut solution = Sum.solve("1,2,3");
+        check(&mut solution, |part| inputs::answer(1, part)).unwrap();
+        assert!(solution.parts.iter().all(|p| p.check == Check::Unknown));
+
+        check(&mut solution, |part| match part {
+            1 => Ok("6".to_string()),
+            _ => Ok("0".to_string()),
+        })
+        .unwrap();
+        assert_eq!(solution.parts[0].check, Check::Correct);
+        assert_eq!(
+            solution.parts[1].check,
+            Check::Wrong {
+                expected: "0".to_string()
+            }
+        );
+    }
+}
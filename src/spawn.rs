@@ -0,0 +1,182 @@
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::Memory;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// What a machine running on its own thread reports to the host.
+/// Every message after the outputs is the last one.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Message {
+    Output(i64),
+    Halted,
+    Failed(IntcodeError),
+    /// The host cancelled the machine or dropped its input.
+    Cancelled,
+}
+
+enum Input {
+    Value(i64),
+    Cancel,
+}
+
+/// A machine running on its own thread, see `spawn`.
+pub struct MachineHandle<M: Memory> {
+    input: Sender<Input>,
+    output: Receiver<Message>,
+    cancelled: Arc<AtomicBool>,
+    thread: JoinHandle<Result<Machine<M>, IntcodeError>>,
+}
+
+/// Runs the machine on a new thread. It reads input sent through the handle,
+/// reports output and how it stopped as messages and never panics.
+pub fn spawn<M: Memory + Send + 'static>(machine: Machine<M>) -> MachineHandle<M> {
+    let (input, rx_input) = mpsc::channel();
+    let (tx_output, output) = mpsc::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let flag = Arc::clone(&cancelled);
+    let thread = thread::spawn(move || run(machine, &rx_input, &tx_output, &flag));
+
+    MachineHandle {
+        input,
+        output,
+        cancelled,
+        thread,
+    }
+}
+
+fn run<M: Memory>(
+    mut machine: Machine<M>,
+    input: &Receiver<Input>,
+    output: &Sender<Message>,
+    cancelled: &AtomicBool,
+) -> Result<Machine<M>, IntcodeError> {
+    //the host may have dropped its handle, then nobody reads the messages anymore
+    let stop = |message: Message| {
+        let _ = output.send(message);
+    };
+
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            stop(Message::Cancelled);
+            return Ok(machine);
+        }
+
+        let result = match machine.step() {
+            Ok(r) => r,
+            Err(e) => {
+                stop(Message::Failed(e.clone()));
+                return Err(e);
+            }
+        };
+
+        for value in machine.take_output() {
+            if output.send(Message::Output(value)).is_err() {
+                return Ok(machine);
+            }
+        }
+
+        match result {
+            ComputeResult::CanContinue => continue,
+            ComputeResult::WaitingForInput => match input.recv() {
+                Ok(Input::Value(value)) => machine.push_input(value),
+                Ok(Input::Cancel) | Err(_) => {
+                    stop(Message::Cancelled);
+                    return Ok(machine);
+                }
+            },
+            ComputeResult::Halt => {
+                stop(Message::Halted);
+                return Ok(machine);
+            }
+        }
+    }
+}
+
+impl<M: Memory> MachineHandle<M> {
+    /// Sends an input value, returns false when the machine has stopped already.
+    pub fn send(&self, value: i64) -> bool {
+        self.input.send(Input::Value(value)).is_ok()
+    }
+
+    /// Waits for the next message, `None` once the last message was received.
+    pub fn recv(&self) -> Option<Message> {
+        self.output.recv().ok()
+    }
+
+    /// Like `recv`, but gives up after `timeout`, e.g. when the machine waits for input.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Message, RecvTimeoutError> {
+        self.output.recv_timeout(timeout)
+    }
+
+    /// Asks the machine to stop before its next instruction.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        //wake it up in case it's waiting for input
+        let _ = self.input.send(Input::Cancel);
+    }
+
+    /// Waits for the machine to stop and returns it in its final state.
+    pub fn join(self) -> Result<Machine<M>, IntcodeError> {
+        //the machine thread doesn't panic, unless the interpreter has a bug
+        self.thread
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::{IntcodeError, Machine};
+    use crate::spawn::{spawn, Message};
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::Duration;
+
+    #[test]
+    fn reports_output_and_halting() {
+        let handle = spawn(Machine::parse("3,9,102,2,9,9,4,9,99").unwrap());
+
+        assert!(handle.send(21));
+        assert_eq!(handle.recv(), Some(Message::Output(42)));
+        assert_eq!(handle.recv(), Some(Message::Halted));
+        assert_eq!(handle.recv(), None);
+        assert!(!handle.send(1));
+
+        let machine = handle.join().unwrap();
+        assert_eq!(machine.memory().as_slice()[9], 42);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let handle = spawn(Machine::parse("42").unwrap());
+        let error = IntcodeError::UnknownOpcode {
+            instruction_pointer: 0,
+            opcode: 42,
+        };
+
+        assert_eq!(handle.recv(), Some(Message::Failed(error.clone())));
+        assert_eq!(handle.join().err(), Some(error));
+    }
+
+    #[test]
+    fn can_be_cancelled() {
+        //waits for input
+        let handle = spawn(Machine::parse("3,0,99").unwrap());
+        assert_eq!(
+            handle.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        handle.cancel();
+        assert_eq!(handle.recv(), Some(Message::Cancelled));
+        assert_eq!(handle.join().unwrap().instruction_pointer(), 0);
+
+        //loops forever
+        let handle = spawn(Machine::parse("1105,1,0").unwrap());
+        handle.cancel();
+        assert_eq!(handle.recv(), Some(Message::Cancelled));
+        assert!(handle.join().is_ok());
+    }
+}
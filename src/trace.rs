@@ -0,0 +1,346 @@
+use crate::disassemble::{Instruction, Operand};
+use crate::intcode::{ComputeResult, IntcodeError, Machine};
+use crate::memory::Memory;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Write};
+
+/// One executed instruction.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Event {
+    pub instruction: Instruction,
+    /// The value of every operand, for the operand that is written to this is the address.
+    pub values: Vec<i64>,
+    /// Memory addresses read by position and relative operands.
+    pub reads: Vec<usize>,
+    /// The address and value written, if any.
+    pub write: Option<(usize, i64)>,
+    pub next_instruction_pointer: u32,
+}
+
+/// Receives every instruction a machine executes while it runs traced.
+pub trait Tracer {
+    fn trace(&mut self, event: &Event);
+}
+
+fn peek<M: Memory>(machine: &Machine<M>, address: usize) -> i64 {
+    machine.memory().read(address)
+}
+
+impl<M: Memory> Machine<M> {
+    /// Like `step`, but reports the executed instruction to the tracer.
+    pub fn step_traced(&mut self, tracer: &mut dyn Tracer) -> Result<ComputeResult, IntcodeError> {
+        let instruction =
+            match Instruction::decode_from(self.memory(), self.instruction_pointer() as usize) {
+                Some(i) => i,
+                //let the machine report what's wrong with it
+                None => return self.step(),
+            };
+
+        let writes_last = matches!(instruction.mnemonic, "add" | "mul" | "in" | "lt" | "eq");
+
+        let mut values = vec![];
+        let mut reads = vec![];
+        let mut write_address = None;
+
+        for (i, operand) in instruction.operands.iter().enumerate() {
+            let address = match operand {
+                Operand::Immediate(value) => {
+                    values.push(*value);
+                    continue;
+                }
+                Operand::Position(address) => *address,
+                Operand::Relative(offset) => offset.wrapping_add(self.relative_base()),
+            };
+
+            if writes_last && i == instruction.operands.len() - 1 {
+                values.push(address);
+                write_address = Some(address as usize).filter(|_| address >= 0);
+            } else {
+                values.push(peek(self, address as usize));
+                if address >= 0 {
+                    reads.push(address as usize);
+                }
+            }
+        }
+
+        let result = self.step()?;
+
+        if result != ComputeResult::WaitingForInput {
+            tracer.trace(&Event {
+                instruction,
+                values,
+                reads,
+                write: write_address.map(|address| (address, peek(self, address))),
+                next_instruction_pointer: self.instruction_pointer(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Like `run_until_input`, but reports every executed instruction to the tracer.
+    pub fn run_traced(&mut self, tracer: &mut dyn Tracer) -> Result<ComputeResult, IntcodeError> {
+        loop {
+            match self.step_traced(tracer)? {
+                ComputeResult::CanContinue => continue,
+                r => break Ok(r),
+            }
+        }
+    }
+}
+
+/// Writes a line per executed instruction, like
+/// `    12: add  [3], #-2, rb+4 | 5, -2, 104 | [104] <- 3`.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(writer: W) -> TraceWriter<W> {
+        TraceWriter {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flushes the trace, reporting the first error that happened while writing it.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.writer.flush().map(|_| self.writer),
+        }
+    }
+}
+
+impl<W: Write> Tracer for TraceWriter<W> {
+    fn trace(&mut self, event: &Event) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let values: Vec<String> = event.values.iter().map(|v| v.to_string()).collect();
+        let write = match event.write {
+            Some((address, value)) => format!(" | [{}] <- {}", address, value),
+            None => String::new(),
+        };
+
+        if let Err(e) = writeln!(
+            self.writer,
+            "{:>6}: {} | {}{}",
+            event.instruction.address,
+            event.instruction,
+            values.join(", "),
+            write
+        ) {
+            self.error = Some(e);
+        }
+    }
+}
+
+/// Aggregates execution counts per address and opcode, jumps that close
+/// loops and the number of reads and writes per memory cell.
+#[derive(Default, Debug)]
+pub struct Profiler {
+    pub executed: HashMap<usize, u64>,
+    pub opcodes: HashMap<&'static str, u64>,
+    /// Taken backward jumps, keyed by (loop start, jump address).
+    pub loops: HashMap<(usize, usize), u64>,
+    pub reads: HashMap<usize, u64>,
+    pub writes: HashMap<usize, u64>,
+}
+
+impl Tracer for Profiler {
+    fn trace(&mut self, event: &Event) {
+        let address = event.instruction.address;
+
+        *self.executed.entry(address).or_insert(0) += 1;
+        *self.opcodes.entry(event.instruction.mnemonic).or_insert(0) += 1;
+
+        let next = event.next_instruction_pointer as usize;
+        if next <= address && event.instruction.mnemonic != "hlt" {
+            *self.loops.entry((next, address)).or_insert(0) += 1;
+        }
+
+        for read in &event.reads {
+            *self.reads.entry(*read).or_insert(0) += 1;
+        }
+        if let Some((write, _)) = event.write {
+            *self.writes.entry(write).or_insert(0) += 1;
+        }
+    }
+}
+
+//the entries with the highest counts first
+fn top<K: Clone + Ord>(counts: &HashMap<K, u64>, n: usize) -> Vec<(K, u64)> {
+    let mut sorted: Vec<(K, u64)> = counts.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    sorted.sort_by(|(k1, v1), (k2, v2)| v2.cmp(v1).then(k1.cmp(k2)));
+    sorted.truncate(n);
+    sorted
+}
+
+const SHADES: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.executed.values().sum()
+    }
+
+    pub fn hottest_addresses(&self, n: usize) -> Vec<(usize, u64)> {
+        top(&self.executed, n)
+    }
+
+    pub fn hottest_opcodes(&self, n: usize) -> Vec<(&'static str, u64)> {
+        top(&self.opcodes, n)
+    }
+
+    pub fn hottest_loops(&self, n: usize) -> Vec<((usize, usize), u64)> {
+        top(&self.loops, n)
+    }
+
+    /// Renders memory access counts, each character covering `cells` cells and
+    /// `width` characters per row, from ' ' (never) to '@' (the most accessed).
+    /// Only the rows with accessed cells are shown, so sparse memory stays small.
+    /// Both `cells` and `width` are at least 1.
+    pub fn heatmap(counts: &HashMap<usize, u64>, cells: usize, width: usize) -> String {
+        let (cells, width) = (cells.max(1), width.max(1));
+
+        let mut buckets = BTreeMap::new();
+        for (address, count) in counts {
+            *buckets.entry(address / cells).or_insert(0) += count;
+        }
+
+        let last = match buckets.keys().next_back() {
+            Some(last) => *last,
+            None => return String::new(),
+        };
+        let most = *buckets.values().max().unwrap_or(&1) as f64;
+
+        let rows: BTreeSet<usize> = buckets.keys().map(|bucket| bucket / width).collect();
+
+        rows.into_iter()
+            .map(|row| {
+                let first = row * width;
+                let shades: String = (first..=last.min(first.saturating_add(width - 1)))
+                    .map(|bucket| match buckets.get(&bucket) {
+                        None | Some(0) => SHADES[0],
+                        Some(&count) => {
+                            let shade = (count as f64 / most * (SHADES.len() - 2) as f64).round();
+                            SHADES[shade as usize + 1]
+                        }
+                    })
+                    .collect();
+
+                format!("{:>6} |{}|\n", first * cells, shades)
+            })
+            .collect()
+    }
+
+    /// A summary of the `n` hottest addresses, opcodes and loops with heatmaps.
+    pub fn report(&self, n: usize) -> String {
+        let mut report = format!("{} instructions executed\n", self.total());
+
+        report.push_str("\nhottest addresses\n");
+        for (address, count) in self.hottest_addresses(n) {
+            report.push_str(&format!("{:>8}: {}\n", address, count));
+        }
+
+        report.push_str("\nopcodes\n");
+        for (mnemonic, count) in self.hottest_opcodes(n) {
+            report.push_str(&format!("{:>8}: {}\n", mnemonic, count));
+        }
+
+        report.push_str("\nhottest loops\n");
+        for ((start, end), count) in self.hottest_loops(n) {
+            report.push_str(&format!("{:>8}: {}..={}\n", count, start, end));
+        }
+
+        report.push_str("\nreads\n");
+        report.push_str(&Profiler::heatmap(&self.reads, 8, 64));
+        report.push_str("\nwrites\n");
+        report.push_str(&Profiler::heatmap(&self.writes, 8, 64));
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assemble::assemble;
+    use crate::intcode::{ComputeResult, Machine};
+    use crate::trace::{Profiler, TraceWriter};
+
+    fn countdown() -> Machine {
+        Machine::new(
+            assemble(
+                "
+                loop:   add [counter], #-1, [counter]
+                        jt [counter], #loop
+                        hlt
+                counter: dw 3",
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn writes_a_trace() {
+        let mut machine = countdown();
+        let mut writer = TraceWriter::new(vec![]);
+
+        assert_eq!(
+            machine.step_traced(&mut writer),
+            Ok(ComputeResult::CanContinue)
+        );
+        assert_eq!(
+            machine.step_traced(&mut writer),
+            Ok(ComputeResult::CanContinue)
+        );
+
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            "     0: add  [8], #-1, [8] | 3, -1, 8 | [8] <- 2
+     4: jt   [8], #0 | 2, 0
+"
+        );
+    }
+
+    #[test]
+    fn profiles_a_loop() {
+        let mut machine = countdown();
+        let mut profiler = Profiler::new();
+
+        assert_eq!(machine.run_traced(&mut profiler), Ok(ComputeResult::Halt));
+
+        assert_eq!(profiler.total(), 7);
+        assert_eq!(profiler.hottest_addresses(1), vec![(0, 3)]);
+        assert_eq!(
+            profiler.hottest_opcodes(3),
+            vec![("add", 3), ("jt", 3), ("hlt", 1)]
+        );
+        assert_eq!(profiler.hottest_loops(1), vec![((0, 4), 2)]);
+        assert_eq!(profiler.reads.get(&8), Some(&6));
+        assert_eq!(profiler.writes.get(&8), Some(&3));
+        assert_eq!(Profiler::heatmap(&profiler.writes, 4, 4), "     0 |  @|\n");
+
+        //only the rows that were accessed, however far apart
+        let sparse = vec![(3, 1), (1_000_000_000_000, 2)].into_iter().collect();
+        assert_eq!(
+            Profiler::heatmap(&sparse, 2, 4),
+            "     0 | +  |\n1000000000000 |@|\n"
+        );
+
+        //zero cells or characters count as one, the last address doesn't overflow a row
+        let edges = vec![(3, 1)].into_iter().collect();
+        assert_eq!(Profiler::heatmap(&edges, 0, 0), "     3 |@|\n");
+        let edges = vec![(usize::MAX, 1)].into_iter().collect();
+        assert_eq!(
+            Profiler::heatmap(&edges, 1, usize::MAX),
+            format!("{} |@|\n", usize::MAX)
+        );
+    }
+}